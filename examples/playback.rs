@@ -8,7 +8,7 @@ use cpal::{
     traits::{DeviceTrait, EventLoopTrait, HostTrait},
     StreamData, UnknownTypeOutputBuffer,
 };
-use sample::{interpolate, signal, Frame, Signal};
+use sample::{interpolate, signal, Signal};
 
 fn main() {
     // Load the input file.
@@ -0,0 +1,62 @@
+//! Builds the seed corpus for the fuzz targets from the files in `tests/vectors`.
+//!
+//! Run with `cargo run --example seed_corpus` from the `fuzz` directory.
+use std::fs;
+use std::path::Path;
+
+/// The number of frames taken from each file.
+const MAX_FRAMES: usize = 64;
+
+/// The number of bytes of each file used to seed `next_frame`.
+const MAX_STREAM_LEN: usize = 64 * 1024;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let corpus = root.join("corpus");
+    for target in &["frame_header", "side_info", "main_data", "next_frame"] {
+        fs::create_dir_all(corpus.join(target))?;
+    }
+
+    for entry in fs::read_dir(root.join("../tests/vectors"))? {
+        let path = entry?.path();
+        let name = match path.file_stem() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => continue,
+        };
+        let data = fs::read(&path)?;
+
+        let stream = &data[..usize::min(data.len(), MAX_STREAM_LEN)];
+        fs::write(corpus.join("next_frame").join(&name), stream)?;
+
+        let frames = split_frames(&data);
+        for (i, frame) in frames.iter().take(MAX_FRAMES).enumerate() {
+            let file_name = format!("{}-{}", name, i);
+            fs::write(corpus.join("frame_header").join(&file_name), &frame[..6])?;
+            fs::write(corpus.join("side_info").join(&file_name), frame)?;
+            fs::write(corpus.join("main_data").join(&file_name), frame)?;
+        }
+        println!("{}: {} frames", path.display(), frames.len());
+    }
+    Ok(())
+}
+
+/// Splits a stream into frames, skipping any data between them.
+///
+/// Free format frames are skipped, as their size must be measured.
+fn split_frames(data: &[u8]) -> Vec<&[u8]> {
+    let mut frames = Vec::new();
+    let mut pos = 0;
+    while pos + 6 <= data.len() {
+        let size = match puremp3::fuzzing::read_frame_header(&data[pos..]) {
+            Ok(header) => puremp3::fuzzing::frame_size(&header),
+            Err(_) => 0,
+        };
+        if size > 6 && pos + size <= data.len() {
+            frames.push(&data[pos..pos + size]);
+            pos += size;
+        } else {
+            pos += 1;
+        }
+    }
+    frames
+}
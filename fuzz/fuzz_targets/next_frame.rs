@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use puremp3::{Error, Mp3Decoder};
+
+fuzz_target!(|data: &[u8]| {
+    let mut decoder = Mp3Decoder::new(data);
+    // Decode until the end of the stream.
+    while let Ok(_) | Err(Error::Mp3Error(_)) = decoder.next_frame() {}
+});
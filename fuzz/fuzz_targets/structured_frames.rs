@@ -0,0 +1,82 @@
+//! Decodes streams of frames with valid headers and arbitrary payloads.
+//!
+//! Random data rarely contains consecutive valid frame headers, so this target
+//! gets past synchronization into the frame decoders much more often.
+#![no_main]
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use puremp3::{Concealment, CrcPolicy, Error, Mp3Decoder};
+
+#[derive(Arbitrary, Debug)]
+struct Stream {
+    sync_frames: u8,
+    crc_policy: u8,
+    concealment: u8,
+    frames: Vec<Frame>,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Frame {
+    version: u8,
+    layer: u8,
+    crc: bool,
+    bitrate: u8,
+    sample_rate: u8,
+    padding: bool,
+    mode: u8,
+    mode_extension: u8,
+    payload: Vec<u8>,
+}
+
+impl Frame {
+    /// Writes the frame, repeating the payload to fill it.
+    fn write(&self, out: &mut Vec<u8>) {
+        let version = [0b00, 0b10, 0b11][usize::from(self.version % 3)];
+        let layer = [0b01, 0b10, 0b11][usize::from(self.layer % 3)];
+        let bitrate = self.bitrate % 15;
+        let header = [
+            0xff,
+            0xe0 | version << 3 | layer << 1 | u8::from(!self.crc),
+            bitrate << 4 | (self.sample_rate % 3) << 2 | u8::from(self.padding) << 1,
+            (self.mode % 4) << 6 | (self.mode_extension % 4) << 4,
+        ];
+        let size = match puremp3::fuzzing::read_frame_header(&header[..]) {
+            // Free format frames take the size of the payload.
+            Ok(header) if bitrate == 0 => {
+                puremp3::fuzzing::frame_size(&header) + usize::min(self.payload.len(), 2048)
+            }
+            Ok(header) => puremp3::fuzzing::frame_size(&header),
+            Err(_) => return,
+        };
+
+        out.extend_from_slice(&header);
+        if self.payload.is_empty() {
+            out.resize(out.len() + size - header.len(), 0);
+        } else {
+            out.extend(self.payload.iter().cycle().take(size - header.len()));
+        }
+    }
+}
+
+fuzz_target!(|stream: Stream| {
+    let mut data = Vec::new();
+    for frame in &stream.frames {
+        frame.write(&mut data);
+    }
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.set_sync_frames(usize::from(stream.sync_frames % 4));
+    decoder.set_crc_policy(match stream.crc_policy % 3 {
+        0 => CrcPolicy::Ignore,
+        1 => CrcPolicy::Conceal,
+        _ => CrcPolicy::Error,
+    });
+    decoder.set_concealment(match stream.concealment % 4 {
+        0 => Concealment::None,
+        1 => Concealment::Silence,
+        2 => Concealment::Repeat,
+        _ => Concealment::Fade,
+    });
+    // Decode until the end of the stream.
+    while let Ok(_) | Err(Error::Mp3Error(_)) = decoder.next_frame() {}
+});
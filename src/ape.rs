@@ -0,0 +1,300 @@
+//! APE tag support.
+//!
+//! APEv1 and APEv2 tags are commonly found at the end of MP3 files, for
+//! example to store ReplayGain values. Tags are read by `Mp3Decoder` as it
+//! reaches them, and are available from `Mp3Decoder::ape_tag`. Tags without a
+//! header are recognised by walking their items to the footer.
+
+use crate::error::{Error, Mp3Error};
+
+/// The size of an APE tag header or footer.
+pub(crate) const APE_HEADER_SIZE: usize = 32;
+
+/// The largest size of an item before its value: the value size, the flags,
+/// and a key of up to 255 characters with its terminator.
+const MAX_ITEM_HEADER_SIZE: usize = 8 + 255 + 1;
+
+/// A parsed APE tag header or footer.
+pub(crate) struct ApeHeader {
+    pub version: u32,
+    /// The size of the tag items and footer, excluding the header.
+    pub size: u32,
+    pub item_count: u32,
+    pub flags: u32,
+}
+
+impl ApeHeader {
+    /// Whether this is the header of the tag rather than the footer.
+    pub fn is_header(&self) -> bool {
+        self.flags & (1 << 29) != 0
+    }
+
+    /// Whether the tag has a footer.
+    pub fn has_footer(&self) -> bool {
+        self.flags & (1 << 30) == 0
+    }
+
+    /// Whether the tag has a header.
+    pub fn has_header(&self) -> bool {
+        self.flags & (1 << 31) != 0
+    }
+}
+
+/// Reads an APE tag header or footer from the start of `data`.
+///
+/// Returns `None` if `data` does not start with an APE header or footer.
+pub(crate) fn read_ape_header(data: &[u8]) -> Option<ApeHeader> {
+    if data.len() < APE_HEADER_SIZE
+        || !data.starts_with(b"APETAGEX")
+        || data[24..32].iter().any(|&b| b != 0)
+    {
+        return None;
+    }
+
+    let header = ApeHeader {
+        version: read_u32_le(&data[8..12]),
+        size: read_u32_le(&data[12..16]),
+        item_count: read_u32_le(&data[16..20]),
+        flags: read_u32_le(&data[20..24]),
+    };
+    // The size includes the footer, so it can only be smaller than a footer
+    // for a header without one.
+    if (header.version == 1000 || header.version == 2000)
+        && header.size as usize
+            >= if header.is_header() && !header.has_footer() {
+                0
+            } else {
+                APE_HEADER_SIZE
+            }
+    {
+        Some(header)
+    } else {
+        None
+    }
+}
+
+/// A parsed APEv1 or APEv2 tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApeTag {
+    /// The version of the tag (1000 for APEv1, 2000 for APEv2).
+    pub version: u32,
+
+    /// The items in the tag, in the order they appear.
+    pub items: Vec<ApeItem>,
+}
+
+/// An item of an APE tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApeItem {
+    /// The key of the item, such as `REPLAYGAIN_TRACK_GAIN`.
+    pub key: String,
+
+    /// The value of the item.
+    pub value: ApeValue,
+}
+
+/// The value of an APE tag item.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApeValue {
+    /// UTF-8 text. Multiple values are separated by null characters.
+    Text(String),
+
+    /// Binary data, such as cover art.
+    Binary(Vec<u8>),
+
+    /// A UTF-8 link to external data.
+    Locator(String),
+}
+
+impl ApeTag {
+    /// Parses an APE tag that starts with a header or ends with a footer.
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let (header, items) = match read_ape_header(data) {
+            Some(header) if header.is_header() => {
+                let items_size = header.size as usize
+                    - if header.has_footer() {
+                        APE_HEADER_SIZE
+                    } else {
+                        0
+                    };
+                let items = data[APE_HEADER_SIZE..]
+                    .get(..items_size)
+                    .ok_or(Error::Mp3Error(Mp3Error::InvalidData(
+                        "APE tag is truncated",
+                    )))?;
+                (header, items)
+            }
+            _ => {
+                let footer_start = data.len().saturating_sub(APE_HEADER_SIZE);
+                let header = read_ape_header(&data[footer_start..])
+                    .ok_or(Error::Mp3Error(Mp3Error::InvalidData("Invalid APE tag")))?;
+                let items_start = (footer_start + APE_HEADER_SIZE)
+                    .checked_sub(header.size as usize)
+                    .ok_or(Error::Mp3Error(Mp3Error::InvalidData(
+                        "APE tag is truncated",
+                    )))?;
+                let items = data.get(items_start..footer_start).ok_or(Error::Mp3Error(
+                    Mp3Error::InvalidData("APE tag is truncated"),
+                ))?;
+                (header, items)
+            }
+        };
+
+        let mut items = items;
+        let mut tag_items = Vec::with_capacity(usize::min(header.item_count as usize, 64));
+        for _ in 0..header.item_count {
+            let (item, rest) = read_item(header.version, items)?;
+            tag_items.push(item);
+            items = rest;
+        }
+
+        Ok(ApeTag {
+            version: header.version,
+            items: tag_items,
+        })
+    }
+
+    /// Returns the item with the given key. Keys are case insensitive.
+    pub fn get(&self, key: &str) -> Option<&ApeItem> {
+        self.items
+            .iter()
+            .find(|item| item.key.eq_ignore_ascii_case(key))
+    }
+
+    /// Returns the value of the text item with the given key.
+    pub fn text(&self, key: &str) -> Option<&str> {
+        match self.get(key).map(|item| &item.value) {
+            Some(ApeValue::Text(text)) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the total size of the APE tag whose footer is at the end of `data`,
+/// including its header if it has one.
+pub(crate) fn ape_tag_size_from_footer(data: &[u8]) -> Option<usize> {
+    let start = data.len().checked_sub(APE_HEADER_SIZE)?;
+    let footer = read_ape_header(&data[start..]).filter(|footer| !footer.is_header())?;
+    let header_size = if footer.has_header() {
+        APE_HEADER_SIZE
+    } else {
+        0
+    };
+    (footer.size as usize).checked_add(header_size)
+}
+
+/// Returns the total size of the APE tag without a header at the start of
+/// `data`, by walking its items until the footer is reached.
+///
+/// Returns `Some(None)` if `data` does not start with such a tag, or `None` if
+/// more data is needed. `finished` marks that no more data is available.
+pub(crate) fn ape_footer_tag_size(data: &[u8], finished: bool) -> Option<Option<usize>> {
+    let mut pos = 0;
+    let mut item_count = 0;
+    loop {
+        let data = data.get(pos..).unwrap_or(&[]);
+        if data.len() < APE_HEADER_SIZE && !finished {
+            return None;
+        }
+        if let Some(footer) = read_ape_header(data) {
+            let tag_size = pos + APE_HEADER_SIZE;
+            let is_footer = item_count > 0
+                && !footer.is_header()
+                && footer.size as usize == tag_size
+                && footer.item_count == item_count;
+            return Some(if is_footer { Some(tag_size) } else { None });
+        }
+        match read_item_size(data, finished)? {
+            Some(size) if pos + size <= u32::MAX as usize => {
+                pos += size;
+                item_count += 1;
+            }
+            _ => return Some(None),
+        }
+    }
+}
+
+/// Reads the header of an item, returning the size of the entire item.
+///
+/// This is stricter than `read_item`, as it is used to tell tags apart from
+/// MP3 data: keys must be 2 to 255 characters long, and undefined flags must
+/// be clear. Returns `None` if more data is needed.
+fn read_item_size(data: &[u8], finished: bool) -> Option<Option<usize>> {
+    if data.len() < 8 {
+        return if finished { Some(None) } else { None };
+    }
+    if read_u32_le(&data[4..8]) & !0b111 != 0 {
+        return Some(None);
+    }
+    let value_size = read_u32_le(&data[0..4]) as usize;
+    let key = &data[8..usize::min(data.len(), MAX_ITEM_HEADER_SIZE)];
+    let is_key_char = |c: &u8| (0x20..0x7f).contains(c);
+    match key.iter().position(|&b| b == 0) {
+        Some(key_len) if key_len >= 2 && key[..key_len].iter().all(is_key_char) => {
+            Some((key_len + 9).checked_add(value_size))
+        }
+        // The rest of the key has not arrived yet.
+        None if key.len() < MAX_ITEM_HEADER_SIZE - 8
+            && !finished
+            && key.iter().all(is_key_char) =>
+        {
+            None
+        }
+        _ => Some(None),
+    }
+}
+
+/// Reads a single item, returning it along with the following data.
+fn read_item(version: u32, data: &[u8]) -> Result<(ApeItem, &[u8]), Error> {
+    if data.len() < 8 {
+        return Err(Error::Mp3Error(Mp3Error::InvalidData(
+            "APE item is truncated",
+        )));
+    }
+    let value_size = read_u32_le(&data[0..4]) as usize;
+    let flags = read_u32_le(&data[4..8]);
+    let data = &data[8..];
+
+    let key_len =
+        data.iter()
+            .position(|&b| b == 0)
+            .ok_or(Error::Mp3Error(Mp3Error::InvalidData(
+                "APE item is truncated",
+            )))?;
+    let key = &data[..key_len];
+    if key.is_empty() || !key.iter().all(|&c| (0x20..0x7f).contains(&c)) {
+        return Err(Error::Mp3Error(Mp3Error::InvalidData(
+            "Invalid APE item key",
+        )));
+    }
+    let data = &data[key_len + 1..];
+
+    if value_size > data.len() {
+        return Err(Error::Mp3Error(Mp3Error::InvalidData(
+            "APE item is truncated",
+        )));
+    }
+    let (value, rest) = data.split_at(value_size);
+
+    // APEv1 items are always text.
+    let item_type = if version == 1000 {
+        0
+    } else {
+        (flags >> 1) & 0b11
+    };
+    let value = match item_type {
+        0 => ApeValue::Text(String::from_utf8_lossy(value).into_owned()),
+        2 => ApeValue::Locator(String::from_utf8_lossy(value).into_owned()),
+        _ => ApeValue::Binary(value.to_vec()),
+    };
+
+    let item = ApeItem {
+        key: key.iter().map(|&c| char::from(c)).collect(),
+        value,
+    };
+    Ok((item, rest))
+}
+
+fn read_u32_le(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
@@ -0,0 +1,76 @@
+use crate::error::{Error, Mp3Error};
+use crate::types::{CrcPolicy, DecoderState, FrameHeader};
+
+/// Computes the CRC-16 checksum used to protect MPEG audio frames.
+///
+/// Uses the polynomial 0x8005 with an initial value of 0xffff.
+pub struct Crc16(u16);
+
+impl Crc16 {
+    pub fn new() -> Self {
+        Crc16(0xffff)
+    }
+
+    /// Adds the first `num_bits` bits of `data` to the checksum.
+    pub fn update_bits(&mut self, data: &[u8], num_bits: usize) {
+        for i in 0..num_bits {
+            let bit = u16::from(data[i / 8] >> (7 - i % 8)) & 1;
+            let msb = self.0 >> 15;
+            self.0 <<= 1;
+            if msb ^ bit != 0 {
+                self.0 ^= 0x8005;
+            }
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.update_bits(data, data.len() * 8);
+    }
+
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+}
+
+/// Updates a CRC-16/ARC checksum, as used by the LAME tag.
+///
+/// This is the bit-reflected form of the polynomial 0x8005 with an initial value of 0.
+pub fn crc16_arc(crc: u16, data: &[u8]) -> u16 {
+    data.iter().fold(crc, |mut crc, &byte| {
+        crc ^= u16::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xa001
+            } else {
+                crc >> 1
+            };
+        }
+        crc
+    })
+}
+
+/// Checks the CRC of a frame unless the decoder's `CrcPolicy` ignores it.
+///
+/// The checksum covers the last two bytes of the frame header, followed by the
+/// first `num_bits` bits of `data`.
+/// Returns `Mp3Error::CrcMismatch` if the frame should be concealed or reported.
+pub fn check_crc(
+    decoder: &DecoderState,
+    header: &FrameHeader,
+    data: &[u8],
+    num_bits: usize,
+) -> Result<(), Error> {
+    let expected = match header.crc_value {
+        Some(crc) if decoder.crc_policy != CrcPolicy::Ignore => crc,
+        _ => return Ok(()),
+    };
+
+    let mut crc = Crc16::new();
+    crc.update(&header.header_bytes[2..]);
+    crc.update_bits(data, num_bits);
+    if crc.value() == expected {
+        Ok(())
+    } else {
+        Err(Error::Mp3Error(Mp3Error::CrcMismatch))
+    }
+}
@@ -5,6 +5,91 @@ use bitstream_io::{BigEndian, BitReader};
 use byteorder::ReadBytesExt;
 use std::io::Read;
 
+const MPEG1_LAYER1_BIT_RATES: [BitRate; 14] = [
+    BitRate::Kbps32,
+    BitRate::Kbps64,
+    BitRate::Kbps96,
+    BitRate::Kbps128,
+    BitRate::Kbps160,
+    BitRate::Kbps192,
+    BitRate::Kbps224,
+    BitRate::Kbps256,
+    BitRate::Kbps288,
+    BitRate::Kbps320,
+    BitRate::Kbps352,
+    BitRate::Kbps384,
+    BitRate::Kbps416,
+    BitRate::Kbps448,
+];
+
+const MPEG1_LAYER2_BIT_RATES: [BitRate; 14] = [
+    BitRate::Kbps32,
+    BitRate::Kbps48,
+    BitRate::Kbps56,
+    BitRate::Kbps64,
+    BitRate::Kbps80,
+    BitRate::Kbps96,
+    BitRate::Kbps112,
+    BitRate::Kbps128,
+    BitRate::Kbps160,
+    BitRate::Kbps192,
+    BitRate::Kbps224,
+    BitRate::Kbps256,
+    BitRate::Kbps320,
+    BitRate::Kbps384,
+];
+
+const MPEG1_LAYER3_BIT_RATES: [BitRate; 14] = [
+    BitRate::Kbps32,
+    BitRate::Kbps40,
+    BitRate::Kbps48,
+    BitRate::Kbps56,
+    BitRate::Kbps64,
+    BitRate::Kbps80,
+    BitRate::Kbps96,
+    BitRate::Kbps112,
+    BitRate::Kbps128,
+    BitRate::Kbps160,
+    BitRate::Kbps192,
+    BitRate::Kbps224,
+    BitRate::Kbps256,
+    BitRate::Kbps320,
+];
+
+const MPEG2_LAYER1_BIT_RATES: [BitRate; 14] = [
+    BitRate::Kbps32,
+    BitRate::Kbps48,
+    BitRate::Kbps56,
+    BitRate::Kbps64,
+    BitRate::Kbps80,
+    BitRate::Kbps96,
+    BitRate::Kbps112,
+    BitRate::Kbps128,
+    BitRate::Kbps144,
+    BitRate::Kbps160,
+    BitRate::Kbps176,
+    BitRate::Kbps192,
+    BitRate::Kbps224,
+    BitRate::Kbps256,
+];
+
+const MPEG2_LAYER23_BIT_RATES: [BitRate; 14] = [
+    BitRate::Kbps8,
+    BitRate::Kbps16,
+    BitRate::Kbps24,
+    BitRate::Kbps32,
+    BitRate::Kbps40,
+    BitRate::Kbps48,
+    BitRate::Kbps56,
+    BitRate::Kbps64,
+    BitRate::Kbps80,
+    BitRate::Kbps96,
+    BitRate::Kbps112,
+    BitRate::Kbps128,
+    BitRate::Kbps144,
+    BitRate::Kbps160,
+];
+
 pub fn read_frame_header<R: Read>(mut data: R) -> Result<FrameHeader, Error> {
     if data.read_u8()? != 0xff {
         return Err(Error::Mp3Error(Mp3Error::InvalidData(
@@ -38,56 +123,25 @@ pub fn read_frame_header<R: Read>(mut data: R) -> Result<FrameHeader, Error> {
         0b110 => MpegLayer::Layer1,
         _ => unreachable!(),
     };
-    if layer != MpegLayer::Layer3 {
-        return Err(Error::Mp3Error(Mp3Error::Unsupported(
-            "Only MPEG Layer III is supported",
-        )));
-    }
 
-    // CRC is ignored for now.
     let crc = byte & 1 == 0;
 
     let mut bytes = [0u8; 2];
     data.read_exact(&mut bytes)?;
 
     let is_version2 = version == MpegVersion::Mpeg2 || version == MpegVersion::Mpeg2_5;
-    let bitrate = match (bytes[0] & 0b1111_0000, is_version2) {
-        (0b0001_0000, false) => BitRate::Kbps32,
-        (0b0010_0000, false) => BitRate::Kbps40,
-        (0b0011_0000, false) => BitRate::Kbps48,
-        (0b0100_0000, false) => BitRate::Kbps56,
-        (0b0101_0000, false) => BitRate::Kbps64,
-        (0b0110_0000, false) => BitRate::Kbps80,
-        (0b0111_0000, false) => BitRate::Kbps96,
-        (0b1000_0000, false) => BitRate::Kbps112,
-        (0b1001_0000, false) => BitRate::Kbps128,
-        (0b1010_0000, false) => BitRate::Kbps160,
-        (0b1011_0000, false) => BitRate::Kbps192,
-        (0b1100_0000, false) => BitRate::Kbps224,
-        (0b1101_0000, false) => BitRate::Kbps256,
-        (0b1110_0000, false) => BitRate::Kbps320,
-
-        (0b0001_0000, true) => BitRate::Kbps8,
-        (0b0010_0000, true) => BitRate::Kbps16,
-        (0b0011_0000, true) => BitRate::Kbps24,
-        (0b0100_0000, true) => BitRate::Kbps32,
-        (0b0101_0000, true) => BitRate::Kbps40,
-        (0b0110_0000, true) => BitRate::Kbps48,
-        (0b0111_0000, true) => BitRate::Kbps56,
-        (0b1000_0000, true) => BitRate::Kbps64,
-        (0b1001_0000, true) => BitRate::Kbps80,
-        (0b1010_0000, true) => BitRate::Kbps96,
-        (0b1011_0000, true) => BitRate::Kbps112,
-        (0b1100_0000, true) => BitRate::Kbps128,
-        (0b1101_0000, true) => BitRate::Kbps144,
-        (0b1110_0000, true) => BitRate::Kbps160,
-
-        (0b0000_0000, _) => {
-            return Err(Error::Mp3Error(Mp3Error::Unsupported(
-                "Free bitrate is unsupported",
-            )))
-        }
-        _ => return Err(Error::Mp3Error(Mp3Error::InvalidData("Invalid bitrate"))),
+    let bitrate_table = match (layer, is_version2) {
+        (MpegLayer::Layer1, false) => &MPEG1_LAYER1_BIT_RATES,
+        (MpegLayer::Layer2, false) => &MPEG1_LAYER2_BIT_RATES,
+        (MpegLayer::Layer3, false) => &MPEG1_LAYER3_BIT_RATES,
+        (MpegLayer::Layer1, true) => &MPEG2_LAYER1_BIT_RATES,
+        (_, true) => &MPEG2_LAYER23_BIT_RATES,
+    };
+    let bitrate = match bytes[0] >> 4 {
+        // The size of free format frames is measured later by the decoder.
+        0b0000 => BitRate::Free(0),
+        0b1111 => return Err(Error::Mp3Error(Mp3Error::InvalidData("Invalid bitrate"))),
+        i => bitrate_table[i as usize - 1],
     };
 
     let sample_rate = match (bytes[0] & 0b0000_1100, version) {
@@ -132,29 +186,19 @@ pub fn read_frame_header<R: Read>(mut data: R) -> Result<FrameHeader, Error> {
         _ => unreachable!(),
     };
 
-    if crc {
-        // Skip CRC for now.
-        data.read_u8()?;
-        data.read_u8()?;
-    }
-
-    let bits_per_sample = match version {
-        MpegVersion::Mpeg1 => 144,
-        MpegVersion::Mpeg2 => 72,
-        MpegVersion::Mpeg2_5 => 72,
+    // The CRC is checked when the frame is decoded.
+    let crc_value = if crc {
+        Some(data.read_u16::<byteorder::BigEndian>()?)
+    } else {
+        None
     };
-    let data_size = (bits_per_sample * bitrate.bps() / sample_rate.hz()
-        + if padding { 1 } else { 0 }
-        - if crc { 2 } else { 0 }
-        - 4) as usize;
-
-    // Skip framesize?
-    // Skip ancillary data...?
 
-    Ok(FrameHeader {
+    // Layer I frames are measured in 4-byte slots.
+    let mut header = FrameHeader {
         version,
         layer,
         crc,
+        crc_value,
         bitrate,
         sample_rate,
         padding,
@@ -163,12 +207,64 @@ pub fn read_frame_header<R: Read>(mut data: R) -> Result<FrameHeader, Error> {
         original,
         emphasis,
 
+        header_bytes: [0xff, byte, bytes[0], bytes[1]],
         sample_rate_table,
-        data_size,
-    })
+        data_size: 0,
+    };
+
+    if bitrate != BitRate::Free(0) {
+        // Layer I frames are measured in 4-byte slots.
+        let frame_size = match layer {
+            MpegLayer::Layer1 => 12 * bitrate.bps() / sample_rate.hz() * 4,
+            _ => header.frame_size_factor() * bitrate.bps() / sample_rate.hz(),
+        };
+        header.set_frame_size(frame_size as usize);
+    }
+
+    Ok(header)
+}
+
+/// The largest free format frame that will be searched for.
+pub const MAX_FREE_FORMAT_FRAME_SIZE: usize = 4096;
+
+/// Measures the size of a free format frame by searching for the header of the
+/// following frame.
+///
+/// `data` must start with the frame with the given `header`, and the following
+/// frame is searched for within `MAX_FREE_FORMAT_FRAME_SIZE` bytes.
+/// Returns the frame size in bytes, excluding any padding.
+pub fn measure_free_format_frame(data: &[u8], header: &FrameHeader) -> Result<usize, Error> {
+    let header_size = header.header_size();
+    let padding_size = if header.padding {
+        header.padding_size()
+    } else {
+        0
+    };
+    let data = &data[..usize::min(data.len(), header_size + MAX_FREE_FORMAT_FRAME_SIZE)];
+    for offset in header_size + padding_size + 1..data.len() {
+        if data[offset] != 0xff {
+            continue;
+        }
+
+        if let Ok(next_header) = read_frame_header(&data[offset..]) {
+            if next_header.bitrate == BitRate::Free(0)
+                && next_header.version == header.version
+                && next_header.layer == header.layer
+                && next_header.sample_rate == header.sample_rate
+                && next_header.crc == header.crc
+                && next_header.channels.num_channels() == header.channels.num_channels()
+            {
+                return Ok(offset - padding_size);
+            }
+        }
+    }
+
+    Err(Error::Mp3Error(Mp3Error::InvalidData(
+        "Free format frame size not found",
+    )))
 }
 
-fn read_side_info<R: Read>(mut data: R, header: &FrameHeader) -> Result<SideInfo, Error> {
+pub fn read_side_info<R: Read>(mut data: R, header: &FrameHeader) -> Result<SideInfo, Error> {
     let mut info: SideInfo = Default::default();
     let mut bytes = [0u8; 32];
     let size = header.side_data_len();
@@ -203,7 +299,7 @@ fn read_side_info<R: Read>(mut data: R, header: &FrameHeader) -> Result<SideInfo
     }
 
     for granule in &mut info.granules[..header.num_granules()] {
-        *granule = read_granule_side_info(&header, &mut reader)?;
+        *granule = read_granule_side_info(header, &mut reader)?;
     }
 
     Ok(info)
@@ -224,9 +320,10 @@ fn read_granule_channel_side_info<R: Read>(
     header: &FrameHeader,
     reader: &mut BitReader<R, BigEndian>,
 ) -> Result<GranuleChannelSideInfo, Error> {
-    let mut info: GranuleChannelSideInfo = Default::default();
-
-    info.part2_3_length = reader.read(12)?;
+    let mut info = GranuleChannelSideInfo {
+        part2_3_length: reader.read(12)?,
+        ..Default::default()
+    };
     info.big_values = reader.read(9)?;
     if info.big_values > 288 {
         return Err(Error::Mp3Error(Mp3Error::InvalidData("big_values > 288")));
@@ -242,7 +339,7 @@ fn read_granule_channel_side_info<R: Read>(
     let window_switching = reader.read_bit()?;
     if window_switching {
         let block_type_id = reader.read::<u8>(2)?;
-        let mixed_block = reader.read_bit()?;
+        info.mixed_block = reader.read_bit()?;
         for region in &mut info.table_select[..2] {
             *region = reader.read(5)?;
         }
@@ -262,7 +359,7 @@ fn read_granule_channel_side_info<R: Read>(
             }
             0b01 => BlockType::Start,
             0b10 => {
-                if mixed_block {
+                if info.mixed_block {
                     BlockType::Mixed
                 } else {
                     BlockType::Short
@@ -272,8 +369,12 @@ fn read_granule_channel_side_info<R: Read>(
             _ => unreachable!(),
         };
 
-        // Mixed blocks are always marked as short.
-        assert!(!mixed_block || info.block_type == BlockType::Short);
+        // Only short blocks can be mixed.
+        if info.mixed_block && info.block_type != BlockType::Mixed {
+            return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                "Mixed block with long block type",
+            )));
+        }
 
         info.region0_count = if info.block_type == BlockType::Short {
             8
@@ -310,11 +411,19 @@ fn read_logical_frame_data<'a, R: Read>(
     header: &FrameHeader,
     side_info: &SideInfo,
 ) -> Result<&'a [u8], Error> {
-    let side_info_size = header.side_data_len();
-    let main_data_size = header.data_size - side_info_size;
+    let main_data_size = main_data_size(header)?;
 
     // Copy main_data_begin bytes from the previous frame(s).
     let main_data_begin = side_info.main_data_begin as usize;
+    if main_data_begin > decoder.frame_buffer_len {
+        // Keep this frame's main data for the following frames.
+        skip_main_data(decoder, reader, header)?;
+        return Err(Error::Mp3Error(Mp3Error::ReservoirUnderflow));
+    }
+    if main_data_begin + main_data_size > decoder.frame_buffer.len() {
+        skip_main_data(decoder, reader, header)?;
+        return Err(Error::Mp3Error(Mp3Error::InvalidData("Frame too large")));
+    }
     let prev_start = decoder.frame_buffer_len - main_data_begin;
     for i in 0..main_data_begin {
         decoder.frame_buffer[i] = decoder.frame_buffer[prev_start + i];
@@ -325,7 +434,75 @@ fn read_logical_frame_data<'a, R: Read>(
     Ok(&decoder.frame_buffer[0..decoder.frame_buffer_len])
 }
 
-fn read_main_data<R: Read>(
+/// Adds the main data of a frame to the bit reservoir without decoding it.
+fn skip_main_data<R: Read>(
+    decoder: &mut DecoderState,
+    mut reader: R,
+    header: &FrameHeader,
+) -> Result<(), Error> {
+    let main_data_size = main_data_size(header)?;
+
+    // Following frames can only refer back to the previous 511 bytes.
+    let prev_len = usize::min(decoder.frame_buffer_len, 511);
+    let prev_start = decoder.frame_buffer_len - prev_len;
+    decoder
+        .frame_buffer
+        .copy_within(prev_start..decoder.frame_buffer_len, 0);
+    decoder.frame_buffer_len = prev_len + main_data_size;
+    if decoder.frame_buffer_len > decoder.frame_buffer.len() {
+        decoder.frame_buffer_len = 0;
+        return Err(Error::Mp3Error(Mp3Error::InvalidData("Frame too large")));
+    }
+    reader.read_exact(&mut decoder.frame_buffer[prev_len..decoder.frame_buffer_len])?;
+    Ok(())
+}
+
+/// Returns the size of the main data in a Layer III frame.
+fn main_data_size(header: &FrameHeader) -> Result<usize, Error> {
+    header
+        .data_size
+        .checked_sub(header.side_data_len())
+        .ok_or(Error::Mp3Error(Mp3Error::InvalidData("Frame too small")))
+}
+
+/// Adds a frame to the bit reservoir without decoding it.
+///
+/// Used to prepare the decoder state when seeking.
+pub fn skip_frame<R: Read>(
+    decoder: &mut DecoderState,
+    mut reader: R,
+    header: &FrameHeader,
+) -> Result<(), Error> {
+    let side_info_size = header.side_data_len();
+    let mut side_info_bytes = [0u8; 32];
+    reader.read_exact(&mut side_info_bytes[..side_info_size])?;
+    match header.layer {
+        MpegLayer::Layer3 => skip_main_data(decoder, reader, header),
+        MpegLayer::Layer1 | MpegLayer::Layer2 => {
+            let mut data = [0u8; MAX_FREE_FORMAT_FRAME_SIZE];
+            let data = data
+                .get_mut(..header.data_size)
+                .ok_or(Error::Mp3Error(Mp3Error::InvalidData("Frame too large")))?;
+            reader.read_exact(data)?;
+            Ok(())
+        }
+    }
+}
+
+/// Reads the `main_data_begin` field from the start of a Layer III frame's side info.
+///
+/// Returns 0 for other layers, which do not use the bit reservoir.
+pub fn read_main_data_begin(header: &FrameHeader, side_info: &[u8]) -> u16 {
+    match (header.layer, side_info) {
+        (MpegLayer::Layer3, [a, b, ..]) if header.version == MpegVersion::Mpeg1 => {
+            u16::from(*a) << 1 | u16::from(*b >> 7)
+        }
+        (MpegLayer::Layer3, [a, ..]) => u16::from(*a),
+        _ => 0,
+    }
+}
+
+pub fn read_main_data<R: Read>(
     reader: &mut BitReader<R, BigEndian>,
     header: &FrameHeader,
     side_info: &SideInfo,
@@ -335,7 +512,7 @@ fn read_main_data<R: Read>(
     for g in 0..header.num_granules() {
         for c in 0..header.channels.num_channels() {
             let bits_read = if header.version == MpegVersion::Mpeg1 {
-                read_scale_factors(reader, g, c, &side_info, &mut data)?
+                read_scale_factors(reader, g, c, side_info, &mut data)?
             } else {
                 read_lfs_scale_factors(
                     reader,
@@ -345,8 +522,11 @@ fn read_main_data<R: Read>(
                 )?
             };
 
-            let huffman_len =
-                u32::from(side_info.granules[g].channels[c].part2_3_length) - bits_read;
+            let huffman_len = u32::from(side_info.granules[g].channels[c].part2_3_length)
+                .checked_sub(bits_read)
+                .ok_or(Error::Mp3Error(Mp3Error::InvalidData(
+                    "Scale factors exceed part2_3_length",
+                )))?;
             data.granules[g].channels[c].count1 = crate::huffman::read_huffman(
                 reader,
                 header,
@@ -381,14 +561,14 @@ fn read_scale_factors<R: Read>(
         if scale_len1 > 0 {
             if channel_info.block_type == BlockType::Mixed {
                 for sfb in &mut channel_data.scalefac_l[..8] {
-                    *sfb = reader.read(scale_len1 as u32)?;
+                    *sfb = reader.read(scale_len1)?;
                     bits_read += scale_len1;
                 }
             }
 
             for sfb in &mut channel_data.scalefac_s[..6] {
                 for window in sfb.iter_mut() {
-                    *window = reader.read(scale_len1 as u32)?;
+                    *window = reader.read(scale_len1)?;
                     bits_read += scale_len1;
                 }
             }
@@ -397,7 +577,7 @@ fn read_scale_factors<R: Read>(
         if scale_len2 > 0 {
             for sfb in &mut channel_data.scalefac_s[6..12] {
                 for window in sfb.iter_mut() {
-                    *window = reader.read(scale_len2 as u32)?;
+                    *window = reader.read(scale_len2)?;
                     bits_read += scale_len2;
                 }
             }
@@ -406,7 +586,7 @@ fn read_scale_factors<R: Read>(
         // Normal window.
         let slices = [(0usize, 6usize), (6, 11), (11, 16), (16, 21)];
         for (i, (start, end)) in slices.iter().enumerate() {
-            let len = if i < 2 { scale_len1 } else { scale_len2 } as u32;
+            let len = if i < 2 { scale_len1 } else { scale_len2 };
             if len > 0 {
                 if granule == 0 || !side_info.scfsi[channel][i] {
                     for sfb in
@@ -463,7 +643,11 @@ fn read_lfs_scale_factors<R: Read>(
                 &lfs_table[1],
             ),
             244..=255 => ([(sfc - 244) / 3, (sfc - 244) % 3, 0, 0], &lfs_table[2]),
-            _ => unreachable!(),
+            _ => {
+                return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                    "Invalid scalefac_compress",
+                )))
+            }
         }
     } else {
         let sfc = u32::from(channel_info.scalefac_compress);
@@ -476,8 +660,12 @@ fn read_lfs_scale_factors<R: Read>(
                 [(sfc - 400) / 20, ((sfc - 400) / 4) % 5, (sfc - 400) % 4, 0],
                 &lfs_table[1],
             ),
-            500..=512 => ([(sfc - 500) / 3, (sfc - 500) % 3, 0, 0], &lfs_table[2]),
-            _ => unreachable!(),
+            500..=511 => ([(sfc - 500) / 3, (sfc - 500) % 3, 0, 0], &lfs_table[2]),
+            _ => {
+                return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                    "Invalid scalefac_compress",
+                )))
+            }
         }
     };
 
@@ -487,7 +675,6 @@ fn read_lfs_scale_factors<R: Read>(
     let mut scalefacs = [0u8; 54];
     let mut i = 0;
     for (&len, &num_blocks) in scale_lens[..].iter().zip(lfs_table.iter()) {
-        assert!(len <= 8);
         if len > 0 {
             for _ in 0..num_blocks {
                 scalefacs[i] = reader.read(len)?;
@@ -527,35 +714,108 @@ fn read_lfs_scale_factors<R: Read>(
     Ok(bits_read)
 }
 
+/// Decodes a frame into `out_samples`, returning the number of samples per channel.
+///
+/// Frames with an invalid CRC return `Mp3Error::CrcMismatch` unless the CRC is
+/// ignored. Under `CrcPolicy::Conceal`, these are replaced using `conceal_frame`.
 pub fn process_frame<R: Read>(
+    decoder: &mut DecoderState,
+    reader: R,
+    header: &FrameHeader,
+    out_samples: &mut FrameSamples,
+) -> Result<usize, Error> {
+    out_samples.clear();
+    match header.layer {
+        MpegLayer::Layer1 => crate::layer1::process_frame(decoder, reader, header, out_samples),
+        MpegLayer::Layer2 => crate::layer2::process_frame(decoder, reader, header, out_samples),
+        MpegLayer::Layer3 => process_layer3_frame(decoder, reader, header, out_samples),
+    }
+}
+
+/// Runs the synthesis filterbanks on a frame of silence, in place of a frame
+/// that could not be decoded.
+///
+/// The output contains the decaying tail of the previous frame, and the
+/// filterbank state stays consistent for the following frame.
+pub fn conceal_frame(
+    decoder: &mut DecoderState,
+    header: &FrameHeader,
+    out_samples: &mut FrameSamples,
+) {
+    use crate::synthesis;
+
+    let num_channels = header.channels.num_channels();
+    for ch in 0..num_channels {
+        match header.layer {
+            MpegLayer::Layer3 => {
+                for gr in 0..header.num_granules() {
+                    let mut samples = [0f32; 576];
+                    synthesis::hybrid_synthesis(
+                        BlockType::Long,
+                        &mut decoder.store[ch],
+                        &mut samples,
+                    );
+                    synthesis::frequency_inversion(&mut samples);
+                    synthesis::subband_synthesis(
+                        &samples,
+                        &mut decoder.sbs_v_vec[ch],
+                        out_samples,
+                        ch,
+                        gr * 576,
+                    );
+                }
+            }
+            MpegLayer::Layer1 | MpegLayer::Layer2 => {
+                for pos in (0..header.num_samples()).step_by(32) {
+                    let out =
+                        synthesis::polyphase_synthesis(&[0f32; 32], &mut decoder.sbs_v_vec[ch]);
+                    out_samples.write(ch, pos, &out);
+                }
+            }
+        }
+    }
+
+    if header.channels == Channels::Mono {
+        out_samples.copy_left_to_right();
+    }
+}
+
+fn process_layer3_frame<R: Read>(
     decoder: &mut DecoderState,
     mut reader: R,
     header: &FrameHeader,
-) -> Result<(usize, [[f32; 1152]; 2]), Error> {
-    let side_info = read_side_info(&mut reader, header)?;
+    out_samples: &mut FrameSamples,
+) -> Result<usize, Error> {
+    let mut side_info_bytes = [0u8; 32];
+    let side_info_size = header.side_data_len();
+    reader.read_exact(&mut side_info_bytes[..side_info_size])?;
+    if let Err(e) = crate::crc::check_crc(decoder, header, &side_info_bytes, side_info_size * 8) {
+        skip_main_data(decoder, &mut reader, header)?;
+        return Err(e);
+    }
+
+    let side_info = match read_side_info(&side_info_bytes[..side_info_size], header) {
+        Ok(side_info) => side_info,
+        Err(e) => {
+            // Keep the bit reservoir in sync for the following frames.
+            skip_main_data(decoder, &mut reader, header)?;
+            return Err(e);
+        }
+    };
     let data_buffer = read_logical_frame_data(decoder, &mut reader, header, &side_info)?;
 
     let mut reader = BitReader::endian(data_buffer, BigEndian);
     let mut main_data = read_main_data(&mut reader, header, &side_info)?;
 
-    let mut out_samples = [[0f32; 1152]; 2];
-    let num_samples = decode_frame(
-        decoder,
-        header,
-        &side_info,
-        &mut main_data,
-        &mut out_samples,
-    )?;
-
-    Ok((num_samples, out_samples))
+    decode_frame(decoder, header, &side_info, &mut main_data, out_samples)
 }
 
-fn decode_frame(
+pub fn decode_frame(
     decoder: &mut DecoderState,
     header: &FrameHeader,
     side_info: &SideInfo,
     main_data: &mut MainData,
-    out_samples: &mut [[f32; 1152]; 2],
+    out_samples: &mut FrameSamples,
 ) -> Result<usize, Error> {
     use crate::{requantize, stereo, synthesis};
 
@@ -564,7 +824,7 @@ fn decode_frame(
             let side_info = &side_info.granules[gr].channels[0];
             let main_data = &mut main_data.granules[gr].channels[0];
 
-            requantize::requantize(header, side_info, main_data);
+            requantize::requantize(header, side_info, main_data)?;
             requantize::reorder(header, side_info, main_data);
             synthesis::antialias(side_info, &mut main_data.samples);
             synthesis::hybrid_synthesis(
@@ -576,18 +836,20 @@ fn decode_frame(
             synthesis::subband_synthesis(
                 &main_data.samples,
                 &mut decoder.sbs_v_vec[0],
-                &mut out_samples[0][gr * 576..(gr + 1) * 576],
+                out_samples,
+                0,
+                gr * 576,
             );
         }
 
-        out_samples[1] = out_samples[0];
+        out_samples.copy_left_to_right();
     } else {
         for gr in 0..header.num_granules() {
             for ch in 0..MAX_CHANNELS {
                 let side_info = &side_info.granules[gr].channels[ch];
                 let main_data = &mut main_data.granules[gr].channels[ch];
 
-                requantize::requantize(header, side_info, main_data);
+                requantize::requantize(header, side_info, main_data)?;
                 requantize::reorder(header, side_info, main_data);
             }
 
@@ -605,7 +867,7 @@ fn decode_frame(
                 );
             }
 
-            for (ch, out_channel) in out_samples.iter_mut().enumerate() {
+            for ch in 0..MAX_CHANNELS {
                 let side_info = &side_info.granules[gr].channels[ch];
                 let main_data = &mut main_data.granules[gr].channels[ch];
 
@@ -619,7 +881,9 @@ fn decode_frame(
                 synthesis::subband_synthesis(
                     &main_data.samples,
                     &mut decoder.sbs_v_vec[ch],
-                    &mut out_channel[gr * 576..(gr + 1) * 576],
+                    out_samples,
+                    ch,
+                    gr * 576,
                 );
             }
         }
@@ -1,4 +1,4 @@
-///! Error types related to MP3 decoding.
+//! Error types related to MP3 decoding.
 use std::{fmt, io};
 
 /// Error that can be raised during MP3 decoding.
@@ -36,6 +36,15 @@ pub enum Mp3Error {
 
     /// An unsupported MP3 feature is used in this MP3 stream.
     Unsupported(&'static str),
+
+    /// The CRC checksum of a frame did not match its data.
+    CrcMismatch,
+
+    /// A frame refers to data in the bit reservoir from frames that were not
+    /// read, such as when decoding starts in the middle of a stream.
+    ///
+    /// The frame is skipped, and decoding can continue with the next frame.
+    ReservoirUnderflow,
 }
 
 impl fmt::Display for Mp3Error {
@@ -43,6 +52,8 @@ impl fmt::Display for Mp3Error {
         match self {
             Mp3Error::InvalidData(s) => write!(f, "Invalid data: {}", s),
             Mp3Error::Unsupported(s) => write!(f, "Unsupported: {}", s),
+            Mp3Error::CrcMismatch => write!(f, "CRC mismatch"),
+            Mp3Error::ReservoirUnderflow => write!(f, "Bit reservoir underflow"),
         }
     }
 }
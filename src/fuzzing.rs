@@ -0,0 +1,56 @@
+//! Entry points into the decoder internals, used by the fuzz targets in `fuzz/`.
+//!
+//! Only available with the `fuzzing` feature. This module is not part of the
+//! public API and may change at any time.
+
+use crate::decoder;
+use crate::error::Error;
+use crate::types::{DecoderState, FrameHeader, FrameSamples, MpegLayer};
+use bitstream_io::{BigEndian, BitReader};
+
+pub use crate::decoder::read_frame_header;
+
+/// Returns the size in bytes of a frame with the given header, including the header.
+///
+/// Returns only the header size for free format frames, which must be measured.
+pub fn frame_size(header: &FrameHeader) -> usize {
+    header.header_size() + header.data_size
+}
+
+/// Parses the side info of the Layer III frame at the start of `data`.
+pub fn read_side_info(data: &[u8]) -> Result<(), Error> {
+    let header = read_frame_header(data)?;
+    if header.layer != MpegLayer::Layer3 {
+        return Ok(());
+    }
+
+    decoder::read_side_info(&data[header.header_size()..], &header)?;
+    Ok(())
+}
+
+/// Parses and decodes the main data of the Layer III frame at the start of `data`.
+///
+/// The main data is read from the bytes following the side info, ignoring the
+/// bit reservoir.
+pub fn read_main_data(data: &[u8]) -> Result<(), Error> {
+    let header = read_frame_header(data)?;
+    if header.layer != MpegLayer::Layer3 {
+        return Ok(());
+    }
+
+    let data = &data[header.header_size()..];
+    let side_info = decoder::read_side_info(data, &header)?;
+    let mut reader = BitReader::endian(&data[header.side_data_len()..], BigEndian);
+    let mut main_data = decoder::read_main_data(&mut reader, &header, &side_info)?;
+
+    let mut decoder = DecoderState::new();
+    let mut out_samples = [[0f32; 1152]; 2];
+    decoder::decode_frame(
+        &mut decoder,
+        &header,
+        &side_info,
+        &mut main_data,
+        &mut FrameSamples::planar(&mut out_samples),
+    )?;
+    Ok(())
+}
@@ -21,17 +21,19 @@ pub fn read_huffman<R: Read>(
     let mut bits_read = 0;
     // ? let bit_pos_end = part_2_start + side_info.part2_3_length - 1;
 
-    let (region1_start, region2_start) =
-        if side_info.block_type == BlockType::Short || side_info.block_type == BlockType::Mixed {
-            (36, 576)
-        } else {
-            (
-                SCALE_FACTOR_BAND_INDICES[header.sample_rate_table].0
-                    [side_info.region0_count as usize + 1],
-                SCALE_FACTOR_BAND_INDICES[header.sample_rate_table].0
-                    [side_info.region0_count as usize + side_info.region1_count as usize + 2],
-            )
-        };
+    let (region1_start, region2_start) = if side_info.block_type == BlockType::Short
+        || side_info.block_type == BlockType::Mixed
+    {
+        (36, 576)
+    } else {
+        // Regions that extend past the last scale factor band end at the last sample.
+        let band_indices = &SCALE_FACTOR_BAND_INDICES[header.sample_rate_table].0;
+        let region_start = |sfb: usize| band_indices.get(sfb).copied().unwrap_or(576);
+        (
+            region_start(side_info.region0_count as usize + 1),
+            region_start(side_info.region0_count as usize + side_info.region1_count as usize + 2),
+        )
+    };
 
     // Read big_values.
     let mut is_pos: usize = 0;
@@ -74,7 +76,8 @@ pub fn read_huffman<R: Read>(
 
     if bits_read < len as usize {
         reader.skip(len - bits_read as u32)?;
-    } else if bits_read > len as usize {
+    } else if bits_read > len as usize && is_pos > is_len {
+        // Discard the quadruple that overran the end of the Huffman data.
         is_pos -= 4;
     }
 
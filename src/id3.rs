@@ -0,0 +1,823 @@
+//! ID3 tag support.
+//!
+//! MP3 files commonly store metadata in an ID3v2 tag at the start of the file.
+//! The raw tag is available from `Mp3Decoder::id3v2` after the first frame has
+//! been decoded, and can be parsed with `Id3v2Tag::parse`.
+//!
+//! Older files may instead end with a 128 byte ID3v1 tag, which is available
+//! from `Mp3Decoder::id3v1` once the decoder reaches the end of the stream,
+//! or up front from `Mp3Decoder::read_id3v1` for seekable readers.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use puremp3::{id3::Id3v2Tag, Mp3Decoder};
+//!
+//! let data = std::fs::read("song.mp3").expect("Could not open file");
+//! let mut decoder = Mp3Decoder::new(&data[..]);
+//! decoder.next_frame().expect("Invalid MP3");
+//! if let Some(tag) = decoder.id3v2() {
+//!     let tag = Id3v2Tag::parse(tag).expect("Invalid ID3v2 tag");
+//!     println!("{:?} by {:?}", tag.title(), tag.artist());
+//! }
+//! ```
+
+use crate::error::{Error, Mp3Error};
+use std::borrow::Cow;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// The size of an ID3v2 header or footer.
+pub(crate) const ID3V2_HEADER_SIZE: usize = 10;
+
+/// Returns the total size of the ID3v2 tag at the start of `data`, including
+/// the header and footer.
+///
+/// `data` must contain at least the 10 byte ID3v2 header.
+/// Returns `None` if `data` does not start with an ID3v2 tag.
+pub(crate) fn id3v2_tag_size(data: &[u8]) -> Option<usize> {
+    if data.len() < ID3V2_HEADER_SIZE
+        || &data[0..3] != b"ID3"
+        || data[3] == 0xff
+        || data[4] == 0xff
+        || data[6..10].iter().any(|&b| b & 0x80 != 0)
+    {
+        return None;
+    }
+
+    let flags = data[5];
+    let has_footer = flags & 0b0001_0000 != 0;
+    let size = read_syncsafe_u32(&data[6..10]) as usize;
+    Some(ID3V2_HEADER_SIZE + size + if has_footer { ID3V2_HEADER_SIZE } else { 0 })
+}
+
+/// The size of an ID3v1 tag.
+pub(crate) const ID3V1_TAG_SIZE: usize = 128;
+
+/// A parsed ID3v2.2, ID3v2.3, or ID3v2.4 tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Id3v2Tag {
+    /// The major version of the tag (2, 3, or 4).
+    pub version: u8,
+
+    /// The revision number of the tag.
+    pub revision: u8,
+
+    /// The frames contained in the tag, in the order they appear.
+    pub frames: Vec<Id3v2Frame>,
+}
+
+/// A single frame of an ID3v2 tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Id3v2Frame {
+    /// The ID of the frame, such as `TIT2`.
+    ///
+    /// The three character IDs used by ID3v2.2 are converted to their
+    /// ID3v2.3 equivalents where one exists.
+    pub id: String,
+
+    /// The decoded contents of the frame.
+    pub content: FrameContent,
+}
+
+/// The contents of an ID3v2 frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameContent {
+    /// A text information frame, such as `TIT2` or `TRCK`.
+    /// ID3v2.4 text frames may contain multiple values.
+    Text(Vec<String>),
+
+    /// A user defined text information frame (`TXXX`).
+    UserText { description: String, value: String },
+
+    /// A comment frame (`COMM`).
+    Comment(Comment),
+
+    /// An attached picture frame (`APIC`).
+    Picture(Picture),
+
+    /// A private frame (`PRIV`).
+    Private { owner: String, data: Vec<u8> },
+
+    /// The raw data of any other frame, or of a compressed or encrypted frame.
+    Unknown(Vec<u8>),
+}
+
+/// A comment in an ID3v2 tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// The ISO-639-2 language code of the comment.
+    pub language: String,
+
+    /// A short description of the comment.
+    pub description: String,
+
+    /// The text of the comment.
+    pub text: String,
+}
+
+/// A picture attached to an ID3v2 tag, such as album art.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picture {
+    /// The MIME type of the picture, such as `image/jpeg`.
+    pub mime_type: String,
+
+    /// The type of the picture, as defined by the ID3v2 spec (e.g. 3 for the front cover).
+    pub picture_type: u8,
+
+    /// A description of the picture.
+    pub description: String,
+
+    /// The image data.
+    pub data: Vec<u8>,
+}
+
+impl Id3v2Tag {
+    /// Parses an ID3v2 tag, including its header.
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let tag_size = id3v2_tag_size(data).ok_or(Error::Mp3Error(Mp3Error::InvalidData(
+            "Invalid ID3v2 header",
+        )))?;
+        if data.len() < tag_size {
+            return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                "ID3v2 tag is truncated",
+            )));
+        }
+
+        let version = data[3];
+        let revision = data[4];
+        let flags = data[5];
+        if !(2..=4).contains(&version) {
+            return Err(Error::Mp3Error(Mp3Error::Unsupported(
+                "Unsupported ID3v2 version",
+            )));
+        }
+        if version == 2 && flags & 0b0100_0000 != 0 {
+            return Err(Error::Mp3Error(Mp3Error::Unsupported(
+                "Compressed ID3v2.2 tags are unsupported",
+            )));
+        }
+
+        // ID3v2.4 applies unsynchronisation to each frame individually.
+        let tag_unsynchronised = flags & 0b1000_0000 != 0;
+        let body_size = read_syncsafe_u32(&data[6..10]) as usize;
+        let mut body = Cow::from(&data[ID3V2_HEADER_SIZE..ID3V2_HEADER_SIZE + body_size]);
+        if tag_unsynchronised && version < 4 {
+            body = Cow::from(remove_unsynchronisation(&body));
+        }
+
+        let mut pos = 0;
+        if version >= 3 && flags & 0b0100_0000 != 0 {
+            // Skip the extended header.
+            if body.len() < 4 {
+                return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                    "ID3v2 extended header is truncated",
+                )));
+            }
+            pos = if version == 3 {
+                read_u32(&body[0..4]) as usize + 4
+            } else {
+                read_syncsafe_u32(&body[0..4]) as usize
+            };
+        }
+
+        let frame_header_size = if version == 2 { 6 } else { 10 };
+        let mut frames = Vec::new();
+        while pos + frame_header_size <= body.len() {
+            let frame_header = &body[pos..pos + frame_header_size];
+            if frame_header[0] == 0 {
+                // The rest of the tag is padding.
+                break;
+            }
+
+            let (id, size, format_flags) = match version {
+                2 => (&frame_header[0..3], read_u24(&frame_header[3..6]), 0),
+                3 => (
+                    &frame_header[0..4],
+                    read_u32(&frame_header[4..8]),
+                    frame_header[9],
+                ),
+                _ => (
+                    &frame_header[0..4],
+                    read_syncsafe_u32(&frame_header[4..8]),
+                    frame_header[9],
+                ),
+            };
+            if !id
+                .iter()
+                .all(|&c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            {
+                return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                    "Invalid ID3v2 frame ID",
+                )));
+            }
+
+            pos += frame_header_size;
+            let size = size as usize;
+            if size > body.len() - pos {
+                return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                    "ID3v2 frame is truncated",
+                )));
+            }
+            let frame_data = &body[pos..pos + size];
+            pos += size;
+
+            let id = frame_id(id);
+            let content = if let Some(frame_data) =
+                read_frame_data(version, format_flags, tag_unsynchronised, frame_data)
+            {
+                read_frame_content(&id, version, &frame_data)?
+            } else {
+                FrameContent::Unknown(frame_data.to_vec())
+            };
+            frames.push(Id3v2Frame { id, content });
+        }
+
+        Ok(Id3v2Tag {
+            version,
+            revision,
+            frames,
+        })
+    }
+
+    /// Returns the first frame with the given ID.
+    pub fn get(&self, id: &str) -> Option<&Id3v2Frame> {
+        self.frames.iter().find(|frame| frame.id == id)
+    }
+
+    /// Returns the first value of the text frame with the given ID.
+    pub fn text(&self, id: &str) -> Option<&str> {
+        match self.get(id).map(|frame| &frame.content) {
+            Some(FrameContent::Text(values)) => values.first().map(String::as_str),
+            _ => None,
+        }
+    }
+
+    /// The title of the track (`TIT2`).
+    pub fn title(&self) -> Option<&str> {
+        self.text("TIT2")
+    }
+
+    /// The lead artist of the track (`TPE1`).
+    pub fn artist(&self) -> Option<&str> {
+        self.text("TPE1")
+    }
+
+    /// The album of the track (`TALB`).
+    pub fn album(&self) -> Option<&str> {
+        self.text("TALB")
+    }
+
+    /// The track number (`TRCK`), possibly followed by the total number of
+    /// tracks, such as `3/12`.
+    pub fn track(&self) -> Option<&str> {
+        self.text("TRCK")
+    }
+
+    /// The year of the recording (`TYER`), or the recording time (`TDRC`) in ID3v2.4 tags.
+    pub fn year(&self) -> Option<&str> {
+        self.text("TYER").or_else(|| self.text("TDRC"))
+    }
+
+    /// Returns the gapless playback info from an iTunes `iTunSMPB` comment.
+    ///
+    /// Returns the number of samples of encoder delay and padding, and the
+    /// number of samples in the original audio.
+    pub fn itunes_smpb(&self) -> Option<(u32, u32, u64)> {
+        let text = self.frames.iter().find_map(|frame| match &frame.content {
+            FrameContent::Comment(comment) if comment.description == "iTunSMPB" => {
+                Some(&comment.text)
+            }
+            FrameContent::UserText { description, value } if description == "iTunSMPB" => {
+                Some(value)
+            }
+            _ => None,
+        })?;
+
+        // The fields are space separated hexadecimal numbers. The first field is unused.
+        let mut fields = text.split_whitespace().skip(1);
+        let delay = u32::from_str_radix(fields.next()?, 16).ok()?;
+        let padding = u32::from_str_radix(fields.next()?, 16).ok()?;
+        let length = u64::from_str_radix(fields.next()?, 16).ok()?;
+        Some((delay, padding, length))
+    }
+
+    /// Returns an iterator over the comments in the tag.
+    pub fn comments(&self) -> impl Iterator<Item = &Comment> {
+        self.frames.iter().filter_map(|frame| match &frame.content {
+            FrameContent::Comment(comment) => Some(comment),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the pictures attached to the tag.
+    pub fn pictures(&self) -> impl Iterator<Item = &Picture> {
+        self.frames.iter().filter_map(|frame| match &frame.content {
+            FrameContent::Picture(picture) => Some(picture),
+            _ => None,
+        })
+    }
+}
+
+/// Converts an ID3v2.2 frame ID to its ID3v2.3 equivalent.
+fn frame_id(id: &[u8]) -> String {
+    let id = match id {
+        b"TT2" => b"TIT2",
+        b"TP1" => b"TPE1",
+        b"TP2" => b"TPE2",
+        b"TAL" => b"TALB",
+        b"TRK" => b"TRCK",
+        b"TYE" => b"TYER",
+        b"TCO" => b"TCON",
+        b"TXX" => b"TXXX",
+        b"COM" => b"COMM",
+        b"PIC" => b"APIC",
+        id => id,
+    };
+    id.iter().map(|&c| char::from(c)).collect()
+}
+
+/// Removes the frame format flags' extra data and undoes unsynchronisation.
+///
+/// Returns `None` if the frame is compressed or encrypted.
+fn read_frame_data(
+    version: u8,
+    format_flags: u8,
+    tag_unsynchronised: bool,
+    data: &[u8],
+) -> Option<Cow<'_, [u8]>> {
+    match version {
+        3 => {
+            if format_flags & 0b1100_0000 != 0 {
+                return None;
+            }
+            let grouped = format_flags & 0b0010_0000 != 0;
+            data.get(if grouped { 1 } else { 0 }..).map(Cow::from)
+        }
+        4 => {
+            if format_flags & 0b0000_1100 != 0 {
+                return None;
+            }
+            let grouped = format_flags & 0b0100_0000 != 0;
+            let has_data_length = format_flags & 0b0000_0001 != 0;
+            let start = if grouped { 1 } else { 0 } + if has_data_length { 4 } else { 0 };
+            let data = data.get(start..)?;
+            if tag_unsynchronised || format_flags & 0b0000_0010 != 0 {
+                Some(Cow::from(remove_unsynchronisation(data)))
+            } else {
+                Some(Cow::from(data))
+            }
+        }
+        _ => Some(Cow::from(data)),
+    }
+}
+
+fn read_frame_content(id: &str, version: u8, data: &[u8]) -> Result<FrameContent, Error> {
+    let content = match id {
+        "TXXX" => {
+            let (&encoding, data) = split_first(data)?;
+            let (description, value) = split_terminated(encoding, data);
+            FrameContent::UserText {
+                description: decode_text(encoding, description)?,
+                value: decode_text(encoding, trim_terminator(encoding, value))?,
+            }
+        }
+
+        _ if id.starts_with('T') => {
+            let (&encoding, mut data) = split_first(data)?;
+            let mut values = Vec::new();
+            while !data.is_empty() {
+                let (value, rest) = split_terminated(encoding, data);
+                values.push(decode_text(encoding, value)?);
+                data = rest;
+            }
+            FrameContent::Text(values)
+        }
+
+        "COMM" => {
+            let (&encoding, data) = split_first(data)?;
+            if data.len() < 3 {
+                return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                    "ID3v2 comment is truncated",
+                )));
+            }
+            let (language, data) = data.split_at(3);
+            let (description, text) = split_terminated(encoding, data);
+            FrameContent::Comment(Comment {
+                language: decode_text(0, language)?,
+                description: decode_text(encoding, description)?,
+                text: decode_text(encoding, trim_terminator(encoding, text))?,
+            })
+        }
+
+        "APIC" => {
+            let (&encoding, data) = split_first(data)?;
+            let (mime_type, data) = if version == 2 {
+                // ID3v2.2 uses a three character image format instead of a MIME type.
+                if data.len() < 3 {
+                    return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                        "ID3v2 picture is truncated",
+                    )));
+                }
+                let (format, data) = data.split_at(3);
+                let mime_type = match &decode_text(0, format)?.to_ascii_lowercase()[..] {
+                    "jpg" => "image/jpeg".to_string(),
+                    format => format!("image/{}", format),
+                };
+                (mime_type, data)
+            } else {
+                let (mime_type, data) = split_terminated(0, data);
+                (decode_text(0, mime_type)?, data)
+            };
+            let (&picture_type, data) = split_first(data)?;
+            let (description, data) = split_terminated(encoding, data);
+            FrameContent::Picture(Picture {
+                mime_type,
+                picture_type,
+                description: decode_text(encoding, description)?,
+                data: data.to_vec(),
+            })
+        }
+
+        "PRIV" => {
+            let (owner, data) = split_terminated(0, data);
+            FrameContent::Private {
+                owner: decode_text(0, owner)?,
+                data: data.to_vec(),
+            }
+        }
+
+        _ => FrameContent::Unknown(data.to_vec()),
+    };
+    Ok(content)
+}
+
+fn split_first(data: &[u8]) -> Result<(&u8, &[u8]), Error> {
+    data.split_first()
+        .ok_or(Error::Mp3Error(Mp3Error::InvalidData(
+            "ID3v2 frame is empty",
+        )))
+}
+
+/// Splits a null terminated string from the start of `data`, returning the
+/// string and the data following the terminator.
+fn split_terminated(encoding: u8, data: &[u8]) -> (&[u8], &[u8]) {
+    if encoding == 1 || encoding == 2 {
+        // UTF-16 strings are terminated by a 16-bit null.
+        for i in (0..data.len().saturating_sub(1)).step_by(2) {
+            if data[i] == 0 && data[i + 1] == 0 {
+                return (&data[..i], &data[i + 2..]);
+            }
+        }
+    } else if let Some(i) = data.iter().position(|&b| b == 0) {
+        return (&data[..i], &data[i + 1..]);
+    }
+    (data, &[])
+}
+
+/// Removes an optional null terminator from the end of a string.
+fn trim_terminator(encoding: u8, data: &[u8]) -> &[u8] {
+    split_terminated(encoding, data).0
+}
+
+/// Decodes a string in one of the ID3v2 text encodings.
+fn decode_text(encoding: u8, data: &[u8]) -> Result<String, Error> {
+    let text = match encoding {
+        // ISO-8859-1
+        0 => data.iter().map(|&b| char::from(b)).collect(),
+
+        // UTF-16 with a byte order mark. Big-endian is assumed if it is missing.
+        1 => match data {
+            [0xff, 0xfe, data @ ..] => decode_utf16(data, u16::from_le_bytes),
+            [0xfe, 0xff, data @ ..] => decode_utf16(data, u16::from_be_bytes),
+            _ => decode_utf16(data, u16::from_be_bytes),
+        },
+
+        // UTF-16BE
+        2 => decode_utf16(data, u16::from_be_bytes),
+
+        // UTF-8
+        3 => String::from_utf8_lossy(data).into_owned(),
+
+        _ => {
+            return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                "Invalid ID3v2 text encoding",
+            )))
+        }
+    };
+    Ok(text)
+}
+
+fn decode_utf16(data: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let units = data.chunks_exact(2).map(|c| from_bytes([c[0], c[1]]));
+    std::char::decode_utf16(units)
+        .map(|c| c.unwrap_or(std::char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Undoes unsynchronisation, which inserts a zero byte after every 0xff byte.
+fn remove_unsynchronisation(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut prev = 0;
+    for &byte in data {
+        if prev != 0xff || byte != 0 {
+            out.push(byte);
+        }
+        prev = byte;
+    }
+    out
+}
+
+/// Reads a big-endian syncsafe integer, where the high bit of each byte is unused.
+fn read_syncsafe_u32(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |n, &byte| (n << 7) | u32::from(byte & 0x7f))
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn read_u24(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]])
+}
+
+/// A parsed ID3v1 or ID3v1.1 tag.
+///
+/// Text fields have any trailing padding removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Id3v1Tag {
+    /// The title of the track.
+    pub title: String,
+
+    /// The artist of the track.
+    pub artist: String,
+
+    /// The album of the track.
+    pub album: String,
+
+    /// The year of the recording.
+    pub year: String,
+
+    /// A comment.
+    pub comment: String,
+
+    /// The track number. Only present in ID3v1.1 tags.
+    pub track: Option<u8>,
+
+    /// The genre of the track as an index into `ID3V1_GENRES`.
+    /// 255 indicates that no genre is set.
+    pub genre: u8,
+}
+
+impl Id3v1Tag {
+    /// Parses a 128 byte ID3v1 tag.
+    ///
+    /// Returns `None` if `data` is not an ID3v1 tag.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() != ID3V1_TAG_SIZE || !data.starts_with(b"TAG") {
+            return None;
+        }
+
+        // ID3v1.1 stores the track number in the last byte of the comment,
+        // preceded by a zero byte.
+        let (comment, track) = match data[125..127] {
+            [0, track] if track != 0 => (&data[97..125], Some(track)),
+            _ => (&data[97..127], None),
+        };
+        Some(Id3v1Tag {
+            title: read_id3v1_text(&data[3..33]),
+            artist: read_id3v1_text(&data[33..63]),
+            album: read_id3v1_text(&data[63..93]),
+            year: read_id3v1_text(&data[93..97]),
+            comment: read_id3v1_text(comment),
+            track,
+            genre: data[127],
+        })
+    }
+
+    /// Returns the name of the genre, if it is set and known.
+    pub fn genre_name(&self) -> Option<&'static str> {
+        ID3V1_GENRES.get(usize::from(self.genre)).copied()
+    }
+}
+
+/// Reads the ID3v1 tag at the end of a seekable stream, if any.
+///
+/// The position of the stream is restored afterwards.
+pub(crate) fn read_id3v1<R: Read + Seek>(reader: &mut R) -> io::Result<Option<Id3v1Tag>> {
+    let pos = reader.stream_position()?;
+    let len = reader.seek(SeekFrom::End(0))?;
+    let mut tag = None;
+    if len >= ID3V1_TAG_SIZE as u64 {
+        let mut data = [0u8; ID3V1_TAG_SIZE];
+        reader.seek(SeekFrom::End(-(ID3V1_TAG_SIZE as i64)))?;
+        reader.read_exact(&mut data)?;
+        tag = Id3v1Tag::parse(&data);
+    }
+    reader.seek(SeekFrom::Start(pos))?;
+    Ok(tag)
+}
+
+/// Decodes an ISO-8859-1 ID3v1 field, which is padded with zeros or spaces.
+fn read_id3v1_text(data: &[u8]) -> String {
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    let text: String = data[..len].iter().map(|&b| char::from(b)).collect();
+    text.trim_end().to_string()
+}
+
+/// The names of the ID3v1 genres, including the Winamp extensions.
+pub const ID3V1_GENRES: [&str; 192] = [
+    "Blues",
+    "Classic Rock",
+    "Country",
+    "Dance",
+    "Disco",
+    "Funk",
+    "Grunge",
+    "Hip-Hop",
+    "Jazz",
+    "Metal",
+    "New Age",
+    "Oldies",
+    "Other",
+    "Pop",
+    "R&B",
+    "Rap",
+    "Reggae",
+    "Rock",
+    "Techno",
+    "Industrial",
+    "Alternative",
+    "Ska",
+    "Death Metal",
+    "Pranks",
+    "Soundtrack",
+    "Euro-Techno",
+    "Ambient",
+    "Trip-Hop",
+    "Vocal",
+    "Jazz+Funk",
+    "Fusion",
+    "Trance",
+    "Classical",
+    "Instrumental",
+    "Acid",
+    "House",
+    "Game",
+    "Sound Clip",
+    "Gospel",
+    "Noise",
+    "AlternRock",
+    "Bass",
+    "Soul",
+    "Punk",
+    "Space",
+    "Meditative",
+    "Instrumental Pop",
+    "Instrumental Rock",
+    "Ethnic",
+    "Gothic",
+    "Darkwave",
+    "Techno-Industrial",
+    "Electronic",
+    "Pop-Folk",
+    "Eurodance",
+    "Dream",
+    "Southern Rock",
+    "Comedy",
+    "Cult",
+    "Gangsta",
+    "Top 40",
+    "Christian Rap",
+    "Pop/Funk",
+    "Jungle",
+    "Native American",
+    "Cabaret",
+    "New Wave",
+    "Psychedelic",
+    "Rave",
+    "Showtunes",
+    "Trailer",
+    "Lo-Fi",
+    "Tribal",
+    "Acid Punk",
+    "Acid Jazz",
+    "Polka",
+    "Retro",
+    "Musical",
+    "Rock & Roll",
+    "Hard Rock",
+    "Folk",
+    "Folk-Rock",
+    "National Folk",
+    "Swing",
+    "Fast Fusion",
+    "Bebop",
+    "Latin",
+    "Revival",
+    "Celtic",
+    "Bluegrass",
+    "Avantgarde",
+    "Gothic Rock",
+    "Progressive Rock",
+    "Psychedelic Rock",
+    "Symphonic Rock",
+    "Slow Rock",
+    "Big Band",
+    "Chorus",
+    "Easy Listening",
+    "Acoustic",
+    "Humour",
+    "Speech",
+    "Chanson",
+    "Opera",
+    "Chamber Music",
+    "Sonata",
+    "Symphony",
+    "Booty Bass",
+    "Primus",
+    "Porn Groove",
+    "Satire",
+    "Slow Jam",
+    "Club",
+    "Tango",
+    "Samba",
+    "Folklore",
+    "Ballad",
+    "Power Ballad",
+    "Rhythmic Soul",
+    "Freestyle",
+    "Duet",
+    "Punk Rock",
+    "Drum Solo",
+    "A Cappella",
+    "Euro-House",
+    "Dance Hall",
+    "Goa",
+    "Drum & Bass",
+    "Club-House",
+    "Hardcore",
+    "Terror",
+    "Indie",
+    "BritPop",
+    "Afro-Punk",
+    "Polsk Punk",
+    "Beat",
+    "Christian Gangsta Rap",
+    "Heavy Metal",
+    "Black Metal",
+    "Crossover",
+    "Contemporary Christian",
+    "Christian Rock",
+    "Merengue",
+    "Salsa",
+    "Thrash Metal",
+    "Anime",
+    "JPop",
+    "Synthpop",
+    "Abstract",
+    "Art Rock",
+    "Baroque",
+    "Bhangra",
+    "Big Beat",
+    "Breakbeat",
+    "Chillout",
+    "Downtempo",
+    "Dub",
+    "EBM",
+    "Eclectic",
+    "Electro",
+    "Electroclash",
+    "Emo",
+    "Experimental",
+    "Garage",
+    "Global",
+    "IDM",
+    "Illbient",
+    "Industro-Goth",
+    "Jam Band",
+    "Krautrock",
+    "Leftfield",
+    "Lounge",
+    "Math Rock",
+    "New Romantic",
+    "Nu-Breakz",
+    "Post-Punk",
+    "Post-Rock",
+    "Psytrance",
+    "Shoegaze",
+    "Space Rock",
+    "Trop Rock",
+    "World Music",
+    "Neoclassical",
+    "Audiobook",
+    "Audio Theatre",
+    "Neue Deutsche Welle",
+    "Podcast",
+    "Indie Rock",
+    "G-Funk",
+    "Dubstep",
+    "Garage Rock",
+    "Psybient",
+];
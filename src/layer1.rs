@@ -0,0 +1,114 @@
+use crate::error::{Error, Mp3Error};
+use crate::synthesis;
+use crate::tables::LAYER12_SCALE_FACTORS;
+use crate::types::{Channels, DecoderState, FrameHeader, FrameSamples};
+use bitstream_io::{BigEndian, BitReader};
+use std::io::Read;
+
+/// The number of time slots in a Layer I frame.
+const NUM_SLOTS: usize = 12;
+
+pub fn process_frame<R: Read>(
+    decoder: &mut DecoderState,
+    mut reader: R,
+    header: &FrameHeader,
+    out_samples: &mut FrameSamples,
+) -> Result<usize, Error> {
+    let mut buffer = [0u8; 4096];
+    if header.data_size > buffer.len() {
+        return Err(Error::Mp3Error(Mp3Error::InvalidData("Frame too large")));
+    }
+    reader.read_exact(&mut buffer[..header.data_size])?;
+    let mut reader = BitReader::endian(&buffer[..header.data_size], BigEndian);
+
+    let num_channels = header.channels.num_channels();
+    let bound = header.joint_stereo_bound();
+
+    // Bit allocation. Subbands above the bound share an allocation.
+    let mut allocation = [[0u8; 32]; 2];
+    for sb in 0..32 {
+        let coded_channels = if sb < bound { num_channels } else { 1 };
+        for channel in &mut allocation[..coded_channels] {
+            channel[sb] = reader.read(4)?;
+            if channel[sb] == 0b1111 {
+                return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                    "Invalid bit allocation",
+                )));
+            }
+        }
+        if sb >= bound {
+            allocation[1][sb] = allocation[0][sb];
+        }
+    }
+
+    // The CRC protects the bit allocation.
+    let crc_bits = 4 * (bound * num_channels + (32 - bound));
+    crate::crc::check_crc(decoder, header, &buffer, crc_bits)?;
+
+    // Scale factors.
+    let mut scale_factors = [[0f32; 32]; 2];
+    for sb in 0..32 {
+        for ch in 0..num_channels {
+            if allocation[ch][sb] != 0 {
+                let index = reader.read::<u8>(6)?;
+                scale_factors[ch][sb] = LAYER12_SCALE_FACTORS[index as usize];
+            }
+        }
+    }
+
+    // Samples.
+    let mut samples = [[[0f32; 32]; NUM_SLOTS]; 2];
+    for slot in 0..NUM_SLOTS {
+        for sb in 0..32 {
+            let coded_channels = if sb < bound { num_channels } else { 1 };
+            for ch in 0..coded_channels {
+                let alloc = allocation[ch][sb];
+                if alloc == 0 {
+                    continue;
+                }
+
+                let bits = u32::from(alloc) + 1;
+                let sample = requantize(reader.read(bits)?, (1 << bits) - 1);
+                if sb < bound {
+                    samples[ch][slot][sb] = sample * scale_factors[ch][sb];
+                } else {
+                    // Intensity stereo: both channels share the sample.
+                    for (ch, channel) in samples[..num_channels].iter_mut().enumerate() {
+                        channel[slot][sb] = sample * scale_factors[ch][sb];
+                    }
+                }
+            }
+        }
+    }
+
+    synthesize(decoder, header, [&samples[0], &samples[1]], out_samples);
+    Ok(header.num_samples())
+}
+
+/// Requantizes a sample code with the given number of quantization steps
+/// to the range (-1.0, 1.0).
+///
+/// This is equivalent to the `C * (s + D)` formula given in the spec.
+pub(crate) fn requantize(code: u32, steps: u32) -> f32 {
+    (2.0 * code as f32 - (steps - 1) as f32) / steps as f32
+}
+
+/// Runs the subband samples for each time slot through the polyphase filterbank.
+pub(crate) fn synthesize(
+    decoder: &mut DecoderState,
+    header: &FrameHeader,
+    samples: [&[[f32; 32]]; 2],
+    out_samples: &mut FrameSamples,
+) {
+    let num_channels = header.channels.num_channels();
+    for (ch, channel) in samples[..num_channels].iter().enumerate() {
+        for (slot, subbands) in channel.iter().enumerate() {
+            let out = synthesis::polyphase_synthesis(subbands, &mut decoder.sbs_v_vec[ch]);
+            out_samples.write(ch, slot * 32, &out);
+        }
+    }
+
+    if header.channels == Channels::Mono {
+        out_samples.copy_left_to_right();
+    }
+}
@@ -0,0 +1,157 @@
+use crate::error::{Error, Mp3Error};
+use crate::layer1::{requantize, synthesize};
+use crate::tables::{
+    LAYER12_SCALE_FACTORS, LAYER2_ALLOCATION_A, LAYER2_ALLOCATION_B, LAYER2_ALLOCATION_C,
+    LAYER2_ALLOCATION_D, LAYER2_ALLOCATION_LSF, LAYER2_QUANT_CLASSES,
+};
+use crate::types::{DecoderState, FrameHeader, FrameSamples, MpegVersion, SampleRate};
+use bitstream_io::{BigEndian, BitReader};
+use std::io::Read;
+
+/// The number of time slots in a Layer II frame.
+const NUM_SLOTS: usize = 36;
+
+pub fn process_frame<R: Read>(
+    decoder: &mut DecoderState,
+    mut reader: R,
+    header: &FrameHeader,
+    out_samples: &mut FrameSamples,
+) -> Result<usize, Error> {
+    let mut buffer = [0u8; 4096];
+    if header.data_size > buffer.len() {
+        return Err(Error::Mp3Error(Mp3Error::InvalidData("Frame too large")));
+    }
+    reader.read_exact(&mut buffer[..header.data_size])?;
+    let mut reader = BitReader::endian(&buffer[..header.data_size], BigEndian);
+
+    let num_channels = header.channels.num_channels();
+    let allocation_table = allocation_table(header);
+    let sblimit = allocation_table.len();
+    let bound = usize::min(header.joint_stereo_bound(), sblimit);
+
+    // Bit allocation. Subbands above the bound share an allocation.
+    let mut allocation = [[0u8; 32]; 2];
+    let mut crc_bits = 0;
+    for (sb, &(bits, _)) in allocation_table.iter().enumerate() {
+        let coded_channels = if sb < bound { num_channels } else { 1 };
+        for channel in &mut allocation[..coded_channels] {
+            channel[sb] = reader.read(bits)?;
+            crc_bits += bits as usize;
+        }
+        if sb >= bound {
+            allocation[1][sb] = allocation[0][sb];
+        }
+    }
+
+    // Scale factor selection information.
+    let mut scfsi = [[0u8; 32]; 2];
+    for sb in 0..sblimit {
+        for ch in 0..num_channels {
+            if allocation[ch][sb] != 0 {
+                scfsi[ch][sb] = reader.read(2)?;
+                crc_bits += 2;
+            }
+        }
+    }
+
+    // The CRC protects the bit allocation and the scfsi.
+    crate::crc::check_crc(decoder, header, &buffer, crc_bits)?;
+
+    // Scale factors. Each of the three parts of the frame has its own scale factor,
+    // but scale factors may be shared between parts as indicated by the scfsi.
+    let mut scale_factors = [[[0f32; 3]; 32]; 2];
+    for sb in 0..sblimit {
+        for ch in 0..num_channels {
+            if allocation[ch][sb] == 0 {
+                continue;
+            }
+
+            let indices = match scfsi[ch][sb] {
+                0b00 => [
+                    reader.read::<u8>(6)?,
+                    reader.read::<u8>(6)?,
+                    reader.read::<u8>(6)?,
+                ],
+                0b01 => {
+                    let (a, b) = (reader.read::<u8>(6)?, reader.read::<u8>(6)?);
+                    [a, a, b]
+                }
+                0b10 => {
+                    let a = reader.read::<u8>(6)?;
+                    [a, a, a]
+                }
+                _ => {
+                    let (a, b) = (reader.read::<u8>(6)?, reader.read::<u8>(6)?);
+                    [a, b, b]
+                }
+            };
+            for (scale_factor, &index) in scale_factors[ch][sb].iter_mut().zip(&indices) {
+                *scale_factor = LAYER12_SCALE_FACTORS[index as usize];
+            }
+        }
+    }
+
+    // Samples are coded in 12 groups of 3 consecutive samples per subband.
+    let mut samples = [[[0f32; 32]; NUM_SLOTS]; 2];
+    for group in 0..NUM_SLOTS / 3 {
+        let part = group / 4;
+        let slot = group * 3;
+        for (sb, &(_, classes)) in allocation_table.iter().enumerate() {
+            let coded_channels = if sb < bound { num_channels } else { 1 };
+            for ch in 0..coded_channels {
+                let alloc = allocation[ch][sb];
+                if alloc == 0 {
+                    continue;
+                }
+
+                let (steps, grouped, bits) =
+                    LAYER2_QUANT_CLASSES[classes[alloc as usize - 1] as usize];
+                let codes = if grouped {
+                    let code: u32 = reader.read(bits)?;
+                    [code % steps, (code / steps) % steps, code / steps / steps]
+                } else {
+                    [reader.read(bits)?, reader.read(bits)?, reader.read(bits)?]
+                };
+
+                for (i, &code) in codes.iter().enumerate() {
+                    let sample = requantize(code, steps);
+                    if sb < bound {
+                        samples[ch][slot + i][sb] = sample * scale_factors[ch][sb][part];
+                    } else {
+                        // Intensity stereo: both channels share the sample.
+                        for (ch, channel) in samples[..num_channels].iter_mut().enumerate() {
+                            channel[slot + i][sb] = sample * scale_factors[ch][sb][part];
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    synthesize(decoder, header, [&samples[0], &samples[1]], out_samples);
+    Ok(header.num_samples())
+}
+
+/// Selects the bit allocation table for a frame.
+///
+/// MPEG-2 LSF streams use a single table, while MPEG-1 streams choose a table
+/// based on the sample rate and the bit rate per channel.
+fn allocation_table(header: &FrameHeader) -> &'static [(u32, &'static [u8])] {
+    if header.version != MpegVersion::Mpeg1 {
+        return &LAYER2_ALLOCATION_LSF;
+    }
+
+    let bitrate_per_channel = header.bitrate.bps() / header.channels.num_channels() as u32;
+    let sample_rate = header.sample_rate;
+    if bitrate_per_channel >= 56_000
+        && (sample_rate == SampleRate::Hz48000 || bitrate_per_channel <= 80_000)
+    {
+        &LAYER2_ALLOCATION_A
+    } else if sample_rate != SampleRate::Hz48000 && bitrate_per_channel >= 96_000 {
+        &LAYER2_ALLOCATION_B
+    } else if sample_rate != SampleRate::Hz32000 && bitrate_per_channel <= 48_000 {
+        &LAYER2_ALLOCATION_C
+    } else {
+        &LAYER2_ALLOCATION_D
+    }
+}
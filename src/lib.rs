@@ -1,7 +1,6 @@
 //! An MP3 decoder implemented in pure Rust.
 //!
-//! Supports MPEG-1, MPEG-2, and MPEG-2.5 Layer III streams.
-//! Layers I and II are currently unsupported.
+//! Supports MPEG-1, MPEG-2, and MPEG-2.5 Layer I, II, and III streams.
 //!
 //! # Example
 //!
@@ -13,21 +12,49 @@
 //! }
 //! ```
 
+pub mod ape;
+mod crc;
 mod decoder;
 mod error;
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
 mod huffman;
+pub mod id3;
+mod layer1;
+mod layer2;
+pub mod lyrics3;
+mod push;
+mod reader;
 mod requantize;
+mod seek;
 mod stereo;
+#[cfg(feature = "async")]
+mod stream;
+mod sync;
 mod synthesis;
 mod tables;
 mod types;
+mod vbr;
 
 pub use crate::error::{Error, Mp3Error};
+pub use crate::push::PushDecoder;
+pub use crate::seek::{FrameIndex, IndexedFrame};
+#[cfg(feature = "async")]
+pub use crate::stream::AsyncMp3Decoder;
 pub use crate::types::{
-    BitRate, Channels, Emphasis, FrameHeader, MpegLayer, MpegVersion, SampleRate,
+    BitRate, Channels, Concealment, CrcPolicy, Emphasis, FrameHeader, LameTag, Length, MpegLayer,
+    MpegVersion, SampleRate, VbriHeader, XingHeader,
 };
 
-use std::io::Read;
+use crate::ape::ApeTag;
+use crate::id3::{Id3v1Tag, Id3v2Tag};
+use crate::lyrics3::Lyrics3Tag;
+use crate::reader::PeekReader;
+use crate::sync::{FrameSync, SyncPoint, TagKind};
+use crate::types::FrameSamples;
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
 
 /// Convenience method to decode an MP3.
 /// Returns the first frame header found in the MP3, and an `Iterator` that
@@ -41,15 +68,47 @@ use std::io::Read;
 /// `Mp3Decoder` driectly.
 pub fn read_mp3<R: Read>(
     reader: R,
+) -> Result<(FrameHeader, impl Iterator<Item = (f32, f32)>), Error> {
+    read_samples(Mp3Decoder::new(reader))
+}
+
+/// Convenience method to decode an MP3 with gapless playback enabled.
+///
+/// This is the same as `read_mp3`, except that the encoder delay and padding
+/// are removed. See `Mp3Decoder::set_gapless`.
+pub fn read_mp3_gapless<R: Read>(
+    reader: R,
 ) -> Result<(FrameHeader, impl Iterator<Item = (f32, f32)>), Error> {
     let mut decoder = Mp3Decoder::new(reader);
-    let mut frame = decoder.next_frame()?;
+    decoder.set_gapless(true);
+    read_samples(decoder)
+}
+
+/// Convenience method to decode an MP3, concealing frames that cannot be
+/// decoded.
+///
+/// This is the same as `read_mp3`, except that invalid frames, including those
+/// that refer to missing data in the bit reservoir, are replaced according to
+/// `concealment` instead of being skipped. See `Mp3Decoder::set_concealment`.
+pub fn read_mp3_with_concealment<R: Read>(
+    reader: R,
+    concealment: Concealment,
+) -> Result<(FrameHeader, impl Iterator<Item = (f32, f32)>), Error> {
+    let mut decoder = Mp3Decoder::new(reader);
+    decoder.set_concealment(concealment);
+    read_samples(decoder)
+}
+
+fn read_samples<R: Read>(
+    mut decoder: Mp3Decoder<R>,
+) -> Result<(FrameHeader, impl Iterator<Item = (f32, f32)>), Error> {
+    let mut frame = decoder.next_valid_frame()?;
     let header = frame.header.clone();
     let mut i = 0;
     let iter = std::iter::from_fn(move || {
         if i >= frame.num_samples {
             i = 0;
-            frame = if let Ok(frame) = decoder.next_frame() {
+            frame = if let Ok(frame) = decoder.next_valid_frame() {
                 frame
             } else {
                 return None;
@@ -62,47 +121,242 @@ pub fn read_mp3<R: Read>(
     Ok((header, iter))
 }
 
+/// The largest number of samples per channel in a frame.
+pub const MAX_FRAME_SAMPLES: usize = 1152;
+
+/// The number of samples of delay added by the Layer III decoder, which is
+/// trimmed along with the encoder delay in gapless mode.
+const DECODER_DELAY: u64 = 529;
+
 /// Decodes MP3 streams.
 pub struct Mp3Decoder<R: Read> {
-    reader: R,
+    reader: PeekReader<R>,
     state: crate::types::DecoderState,
+    started: bool,
+    id3v2: Option<Vec<u8>>,
+    id3v1: Option<Id3v1Tag>,
+    ape_tag: Option<ApeTag>,
+    lyrics3: Option<Lyrics3Tag>,
+    xing_header: Option<XingHeader>,
+    lame_tag: Option<LameTag>,
+    vbri_header: Option<VbriHeader>,
+    gapless: bool,
+    gapless_range: Option<(u64, Option<u64>)>,
+    decoded_samples: u64,
+    seek_target: u64,
+    frame_index: Option<FrameIndex>,
+    stream_start: u64,
+    audio_start: u64,
+    sync: FrameSync,
+    skipped_bytes: u64,
+    concealment: Concealment,
+    last_granule: ([[f32; 576]; 2], usize),
+    fade_in: bool,
 }
 
 impl<R: Read> Mp3Decoder<R> {
     /// Creates a new `MP3Decoder` from the given reader.
     pub fn new(reader: R) -> Self {
         Self {
-            reader,
+            reader: PeekReader::new(reader),
             state: crate::types::DecoderState::new(),
+            started: false,
+            id3v2: None,
+            id3v1: None,
+            ape_tag: None,
+            lyrics3: None,
+            xing_header: None,
+            lame_tag: None,
+            vbri_header: None,
+            gapless: false,
+            gapless_range: None,
+            decoded_samples: 0,
+            seek_target: 0,
+            frame_index: None,
+            stream_start: 0,
+            audio_start: 0,
+            sync: FrameSync::new(),
+            skipped_bytes: 0,
+            concealment: Concealment::None,
+            last_granule: ([[0f32; 576]; 2], 0),
+            fade_in: false,
         }
     }
 
     /// Gets a reference to the underlying reader.
     pub fn get_ref(&self) -> &R {
-        &self.reader
+        self.reader.get_ref()
     }
 
     /// Gets a mutable reference to the underlying reader.
     ///
     /// It is inadvisable to directly read from the underlying reader.
     pub fn get_mut(&mut self) -> &mut R {
-        &mut self.reader
+        self.reader.get_mut()
     }
 
     /// Unwraps the `Mp3Decoder`, returning the underlying reader.
+    ///
+    /// Any data that the decoder has read ahead from the reader is lost.
     pub fn into_inner(self) -> R {
-        self.reader
+        self.reader.into_inner()
+    }
+
+    /// Returns the raw bytes of the ID3v2 tag at the start of the stream,
+    /// including the tag header.
+    ///
+    /// The tag is read along with the first frame, so this returns `None` until
+    /// `next_frame` has been called.
+    /// The tag can be parsed with `id3::Id3v2Tag::parse`.
+    pub fn id3v2(&self) -> Option<&[u8]> {
+        self.id3v2.as_deref()
+    }
+
+    /// Returns the ID3v1 tag at the end of the stream.
+    ///
+    /// The tag is read when the decoder reaches the end of the stream, so this
+    /// returns `None` until then. Use `read_id3v1` to read the tag up front from
+    /// a seekable reader.
+    pub fn id3v1(&self) -> Option<&Id3v1Tag> {
+        self.id3v1.as_ref()
+    }
+
+    /// Returns the APE tag found in the stream.
+    ///
+    /// APE tags are read when the decoder reaches them, so this returns `None`
+    /// until then. Tags without a header are found by walking their items to
+    /// the footer.
+    pub fn ape_tag(&self) -> Option<&ApeTag> {
+        self.ape_tag.as_ref()
+    }
+
+    /// Returns the Lyrics3v2 tag found in the stream.
+    ///
+    /// Lyrics3v2 tags are read when the decoder reaches them, so this returns
+    /// `None` until then.
+    pub fn lyrics3(&self) -> Option<&Lyrics3Tag> {
+        self.lyrics3.as_ref()
+    }
+
+    /// Returns the Xing or Info header from the first frame of the stream.
+    ///
+    /// The header is read along with the first frame, so this returns `None`
+    /// until `next_frame` has been called.
+    pub fn xing_header(&self) -> Option<&XingHeader> {
+        self.xing_header.as_ref()
+    }
+
+    /// Returns the VBRI header from the first frame of the stream.
+    ///
+    /// The header is read along with the first frame, so this returns `None`
+    /// until `next_frame` has been called.
+    pub fn vbri_header(&self) -> Option<&VbriHeader> {
+        self.vbri_header.as_ref()
+    }
+
+    /// Returns the LAME tag from the first frame of the stream.
+    ///
+    /// The tag is read along with the first frame, so this returns `None`
+    /// until `next_frame` has been called. Tags with an invalid CRC are ignored.
+    pub fn lame_tag(&self) -> Option<&LameTag> {
+        self.lame_tag.as_ref()
+    }
+
+    /// Returns how frames that fail the CRC check are handled.
+    pub fn crc_policy(&self) -> CrcPolicy {
+        self.state.crc_policy
+    }
+
+    /// Sets how frames that fail the CRC check are handled.
+    ///
+    /// Defaults to `CrcPolicy::Ignore`.
+    pub fn set_crc_policy(&mut self, policy: CrcPolicy) {
+        self.state.crc_policy = policy;
+    }
+
+    /// Returns whether gapless playback is enabled.
+    pub fn gapless(&self) -> bool {
+        self.gapless
+    }
+
+    /// Sets whether gapless playback is enabled.
+    ///
+    /// When enabled, the encoder delay and padding are removed from the
+    /// decoded audio, using the LAME tag or an iTunes `iTunSMPB` comment.
+    /// The LAME encoder delay is extended by the 529 samples of delay added by
+    /// the decoder. Frames that are entirely trimmed are skipped, so
+    /// `Frame::num_samples` may be smaller than usual.
+    ///
+    /// Padding is only removed if the total length of the stream is known.
+    /// Defaults to `false`.
+    pub fn set_gapless(&mut self, gapless: bool) {
+        self.gapless = gapless;
+    }
+
+    /// Returns the number of consecutive frames that must be found before the
+    /// decoder syncs to the stream.
+    pub fn sync_frames(&self) -> usize {
+        self.sync.sync_frames
+    }
+
+    /// Sets the number of consecutive frames that must be found before the
+    /// decoder syncs to the stream.
+    ///
+    /// When the decoder is not in sync, such as at the start of the stream or
+    /// after invalid data, it scans the stream byte by byte for a frame header.
+    /// A header is only accepted if it is followed by `frames - 1` further
+    /// frames with the same version, layer, and sample rate, or by the end of
+    /// the stream. This avoids false syncs in album art and other junk data.
+    /// Once in sync, each following frame only needs to match the previous one.
+    ///
+    /// A value of 1 accepts the first valid frame header. Defaults to 3.
+    pub fn set_sync_frames(&mut self, frames: usize) {
+        self.sync.sync_frames = frames;
+    }
+
+    /// Returns how frames that cannot be decoded are concealed.
+    pub fn concealment(&self) -> Concealment {
+        self.concealment
+    }
+
+    /// Sets how frames that cannot be decoded are concealed.
+    ///
+    /// CRC mismatches are handled by the `CrcPolicy` instead.
+    /// Defaults to `Concealment::None`.
+    pub fn set_concealment(&mut self, concealment: Concealment) {
+        self.concealment = concealment;
+    }
+
+    /// Returns the total number of bytes skipped while searching for frames.
+    ///
+    /// Tags are not counted as skipped.
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped_bytes
     }
 
     /// Returns an `Iterator` that yields MP3 `Frame`s.
     ///
     /// Each `Frame` contains header information and the decoded samples.
     /// Any invalid data is skipped. The iterator will provide `Frame`s until
-    /// there is no more valid MP3 data or an error occurs.
+    /// there is no more valid MP3 data or an error occurs. Frames that refer to
+    /// missing data in the bit reservoir are skipped, unless a `Concealment`
+    /// is set, in which case they are concealed.
     ///
     /// If you wish to inspect any errors, Use `next_frame` instead.
     pub fn frames(mut self) -> impl Iterator<Item = Frame> {
-        std::iter::from_fn(move || self.next_frame().ok())
+        std::iter::from_fn(move || self.next_valid_frame().ok())
+    }
+
+    /// Decodes the next `Frame`, skipping frames that refer to missing data in
+    /// the bit reservoir. These frames are concealed by `next_frame` instead
+    /// when a `Concealment` is set.
+    fn next_valid_frame(&mut self) -> Result<Frame, Error> {
+        loop {
+            match self.next_frame() {
+                Err(Error::Mp3Error(Mp3Error::ReservoirUnderflow)) => (),
+                result => return result,
+            }
+        }
     }
 
     /// Decodes the next MP3 `Frame` in the stream.
@@ -110,33 +364,526 @@ impl<R: Read> Mp3Decoder<R> {
     /// Data is read until a valid `Frame` is found. Invalid data is skipped.
     /// Other errors are returned.
     pub fn next_frame(&mut self) -> Result<Frame, Error> {
-        let header;
+        let mut samples = [[0f32; MAX_FRAME_SAMPLES]; 2];
+        let info = self.next_frame_into(&mut FrameSamples::planar(&mut samples))?;
+        Ok(Frame {
+            header: info.header,
+            samples,
+            num_samples: info.num_samples,
+            skipped_bytes: info.skipped_bytes,
+            concealed: info.concealed,
+        })
+    }
+
+    /// Decodes the next frame into a caller-provided buffer of interleaved
+    /// left and right samples.
+    ///
+    /// This avoids copying the samples of each `Frame`. Returns the header of
+    /// the frame and the number of samples per channel, which is at most
+    /// `MAX_FRAME_SAMPLES`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidInput` if `samples` holds fewer than
+    /// `2 * MAX_FRAME_SAMPLES` samples.
+    pub fn decode_frame_into(
+        &mut self,
+        samples: &mut [f32],
+    ) -> Result<(FrameHeader, usize), Error> {
+        check_buffer_len(samples, 2 * MAX_FRAME_SAMPLES)?;
+        let info = self.next_frame_into(&mut FrameSamples::Interleaved(samples))?;
+        Ok((info.header, info.num_samples))
+    }
+
+    /// Decodes the next frame into caller-provided buffers for the left and
+    /// right channels.
+    ///
+    /// See `decode_frame_into`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidInput` if either buffer holds fewer
+    /// than `MAX_FRAME_SAMPLES` samples.
+    pub fn decode_frame_into_planar(
+        &mut self,
+        left: &mut [f32],
+        right: &mut [f32],
+    ) -> Result<(FrameHeader, usize), Error> {
+        check_buffer_len(left, MAX_FRAME_SAMPLES)?;
+        check_buffer_len(right, MAX_FRAME_SAMPLES)?;
+        let info = self.next_frame_into(&mut FrameSamples::Planar([left, right]))?;
+        Ok((info.header, info.num_samples))
+    }
+
+    /// Decodes the next frame that has samples remaining after trimming.
+    fn next_frame_into(&mut self, samples: &mut FrameSamples) -> Result<FrameInfo, Error> {
         loop {
-            match decoder::read_frame_header(&mut self.reader) {
-                Ok(frame_header) => {
-                    header = frame_header;
-                    break;
-                }
-                Err(Error::Mp3Error(Mp3Error::InvalidData(_))) => (),
-                Err(e) => return Err(e),
+            let mut info = self.decode_next_frame(samples)?;
+            info.num_samples = self.trim_frame(&info.header, samples, info.num_samples);
+            if info.num_samples > 0 {
+                return Ok(info);
             }
         }
+    }
 
-        let (num_samples, samples) =
-            decoder::process_frame(&mut self.state, &mut self.reader, &header)?;
+    fn decode_next_frame(&mut self, samples: &mut FrameSamples) -> Result<FrameInfo, Error> {
+        let skipped_bytes = self.skipped_bytes;
+        let header = if self.started {
+            self.find_frame_header()?
+        } else {
+            self.read_stream_start()?
+        };
 
-        Ok(Frame {
+        // The frame is decoded from peeked data, so that it can be skipped as
+        // a whole if it is invalid.
+        let data = self.reader.peek(header.data_size)?;
+        let result = decoder::process_frame(&mut self.state, data, &header, samples);
+        match self.skip_frame_data(&header) {
+            Ok(()) => (),
+            // A frame truncated by the end of the stream is still decoded or
+            // concealed from the data that is present.
+            Err(Error::IoError(ref e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => (),
+            Err(e) => return Err(e),
+        }
+
+        let (num_samples, concealed) = match result {
+            Ok(num_samples) => (num_samples, false),
+            Err(Error::Mp3Error(Mp3Error::CrcMismatch))
+                if self.state.crc_policy == CrcPolicy::Conceal =>
+            {
+                decoder::conceal_frame(&mut self.state, &header, samples);
+                (header.num_samples(), true)
+            }
+            Err(ref e) if self.concealment != Concealment::None && is_concealable(e) => {
+                self.conceal_frame(&header, samples);
+                (header.num_samples(), true)
+            }
+            Err(e) => {
+                self.decoded_samples += header.num_samples() as u64;
+                return Err(e);
+            }
+        };
+        if !concealed {
+            self.finish_frame(samples, num_samples);
+        }
+        Ok(FrameInfo {
             header,
-            samples,
             num_samples,
+            skipped_bytes: self.skipped_bytes - skipped_bytes,
+            concealed,
         })
     }
+
+    /// Outputs samples in place of a frame that could not be decoded.
+    fn conceal_frame(&mut self, header: &FrameHeader, samples: &mut FrameSamples) {
+        decoder::conceal_frame(&mut self.state, header, samples);
+        let (ref last_granule, len) = self.last_granule;
+        let fade = self.concealment == Concealment::Fade;
+        if len == 0 || self.concealment == Concealment::Silence || (fade && self.fade_in) {
+            return;
+        }
+
+        let num_samples = header.num_samples();
+        for (ch, last_channel) in last_granule.iter().enumerate() {
+            let repeated = last_channel[..len].iter().cycle();
+            for (i, &last) in repeated.take(num_samples).enumerate() {
+                *samples.get_mut(ch, i) = if fade {
+                    last * (1.0 - i as f32 / num_samples as f32)
+                } else {
+                    last
+                };
+            }
+        }
+        self.fade_in = fade;
+    }
+
+    /// Fades in a frame after concealment, and keeps its last granule for
+    /// concealing later frames.
+    fn finish_frame(&mut self, samples: &mut FrameSamples, num_samples: usize) {
+        if self.fade_in {
+            self.fade_in = false;
+            let len = usize::min(num_samples, 576);
+            for ch in 0..2 {
+                for i in 0..len {
+                    *samples.get_mut(ch, i) *= i as f32 / len as f32;
+                }
+            }
+        }
+
+        let len = usize::min(num_samples, 576);
+        let start = num_samples - len;
+        for (ch, last_channel) in self.last_granule.0.iter_mut().enumerate() {
+            for (i, last) in last_channel[..len].iter_mut().enumerate() {
+                *last = samples.get(ch, start + i);
+            }
+        }
+        self.last_granule.1 = len;
+    }
+
+    /// Reads the tags and VBR header at the start of the stream.
+    ///
+    /// Returns the header of the first audio frame.
+    fn read_stream_start(&mut self) -> Result<FrameHeader, Error> {
+        self.started = true;
+        self.read_id3v2()?;
+        let mut header = self.find_frame_header()?;
+        self.stream_start = self.reader.position() - header.header_size() as u64;
+        if self.read_vbr_header(&header)? {
+            // The frame containing the VBR header holds no audio.
+            header = self.find_frame_header()?;
+        }
+        self.audio_start = self.reader.position() - header.header_size() as u64;
+        Ok(header)
+    }
+
+    /// Removes any samples before the seek target from a frame, as well as the
+    /// encoder delay and padding in gapless mode.
+    ///
+    /// Returns the number of samples remaining, which is 0 if the entire frame
+    /// was trimmed.
+    fn trim_frame(
+        &mut self,
+        header: &FrameHeader,
+        samples: &mut FrameSamples,
+        num_samples: usize,
+    ) -> usize {
+        let (start, end) = if self.gapless {
+            self.gapless_range(header)
+        } else {
+            (0, None)
+        };
+        let start = u64::max(start, self.seek_target);
+
+        let frame_start = self.decoded_samples;
+        let frame_end = frame_start + num_samples as u64;
+        self.decoded_samples = frame_end;
+
+        let keep_start = start.clamp(frame_start, frame_end);
+        let keep_end = end.map_or(frame_end, |end| end.clamp(keep_start, frame_end));
+        let keep_start = (keep_start - frame_start) as usize;
+        let keep_end = (keep_end - frame_start) as usize;
+        if keep_start > 0 {
+            samples.move_to_start(keep_start..keep_end);
+        }
+        keep_end - keep_start
+    }
+
+    /// Returns the range of samples that remain after removing the encoder
+    /// delay and padding, which is found once per stream.
+    fn gapless_range(&mut self, header: &FrameHeader) -> (u64, Option<u64>) {
+        match self.gapless_range {
+            Some(range) => range,
+            None => {
+                let range = self.find_gapless_range(header);
+                self.gapless_range = Some(range);
+                range
+            }
+        }
+    }
+
+    /// Returns the range of samples that remain after removing the encoder
+    /// delay and padding. The end is unknown if the length of the stream is unknown.
+    fn find_gapless_range(&self, header: &FrameHeader) -> (u64, Option<u64>) {
+        if let Some(lame_tag) = &self.lame_tag {
+            let delay = u64::from(lame_tag.encoder_delay);
+            let padding = u64::from(lame_tag.encoder_padding);
+            let start = delay + DECODER_DELAY;
+            let end = self
+                .xing_header
+                .as_ref()
+                .and_then(|xing_header| xing_header.frames)
+                .map(|frames| {
+                    let total_samples = u64::from(frames) * header.num_samples() as u64;
+                    start + total_samples.saturating_sub(delay + padding)
+                });
+            return (start, end);
+        }
+
+        // iTunes includes the decoder delay in its encoder delay.
+        let itunes_smpb = self
+            .id3v2
+            .as_deref()
+            .and_then(|tag| Id3v2Tag::parse(tag).ok())
+            .and_then(|tag| tag.itunes_smpb());
+        if let Some((delay, _, length)) = itunes_smpb {
+            let start = u64::from(delay);
+            return (start, Some(start + length));
+        }
+
+        (0, None)
+    }
+
+    /// Reads data until a valid frame header is found. Invalid data is skipped,
+    /// and any APE, Lyrics3v2, or ID3v1 tags are read.
+    ///
+    /// Headers are parsed from peeked data, so the search continues at the
+    /// next byte after a false sync.
+    fn find_frame_header(&mut self) -> Result<FrameHeader, Error> {
+        loop {
+            let sync = &self.sync;
+            let free_format_size = &mut self.state.free_format_size;
+            let sync_point = self
+                .reader
+                .scan(|data, finished| sync.next(data, finished, free_format_size))??;
+            match sync_point {
+                SyncPoint::Frame(header) => {
+                    self.reader
+                        .read_exact(&mut [0u8; 6][..header.header_size()])?;
+                    self.sync.sync_header = Some(header.clone());
+                    return Ok(header);
+                }
+                SyncPoint::Tag(kind, size) => self.read_trailing_tag(kind, size)?,
+                SyncPoint::Invalid => {
+                    self.reader.read_exact(&mut [0u8])?;
+                    self.sync.sync_header = None;
+                    self.skipped_bytes += 1;
+                }
+            }
+        }
+    }
+
+    /// Reads the VBR header from the first frame, if it contains one.
+    ///
+    /// Returns `true` if a header was found, in which case the rest of the
+    /// frame has been skipped.
+    fn read_vbr_header(&mut self, header: &FrameHeader) -> Result<bool, Error> {
+        if header.layer != MpegLayer::Layer3 {
+            return Ok(false);
+        }
+
+        let data = self.reader.peek(header.data_size)?;
+        if let Some((xing_header, end)) = vbr::read_xing_header(header, data) {
+            self.lame_tag = vbr::read_lame_tag(header, data, end);
+            self.xing_header = Some(xing_header);
+        } else if let Some(vbri_header) = vbr::read_vbri_header(header, data) {
+            self.vbri_header = Some(vbri_header);
+        } else {
+            return Ok(false);
+        }
+
+        self.skip_frame_data(header)?;
+        Ok(true)
+    }
+
+    /// Skips the data of a frame whose header has been read.
+    fn skip_frame_data(&mut self, header: &FrameHeader) -> Result<(), Error> {
+        let len = header.data_size as u64;
+        if std::io::copy(&mut (&mut self.reader).take(len), &mut std::io::sink())? < len {
+            return Err(Error::IoError(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(())
+    }
+
+    /// Reads any ID3v2 tags at the current position in the stream.
+    ///
+    /// The first tag is kept, and any following tags are skipped.
+    fn read_id3v2(&mut self) -> Result<(), Error> {
+        while let Some(size) = id3::id3v2_tag_size(self.reader.peek(id3::ID3V2_HEADER_SIZE)?) {
+            // The size is read from the stream, so only the bytes that are
+            // present are buffered.
+            let mut tag = Vec::new();
+            (&mut self.reader).take(size as u64).read_to_end(&mut tag)?;
+            if tag.len() < size {
+                return Err(Error::IoError(std::io::ErrorKind::UnexpectedEof.into()));
+            }
+            if self.id3v2.is_none() {
+                self.id3v2 = Some(tag);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a tag of the given kind and size that follows the MP3 data.
+    ///
+    /// The first APE tag is kept.
+    fn read_trailing_tag(&mut self, kind: TagKind, size: usize) -> Result<(), Error> {
+        let mut tag = Vec::new();
+        (&mut self.reader).take(size as u64).read_to_end(&mut tag)?;
+        match kind {
+            TagKind::Ape => {
+                if self.ape_tag.is_none() {
+                    self.ape_tag = ApeTag::parse(&tag).ok();
+                }
+            }
+            TagKind::Lyrics3 => self.lyrics3 = Lyrics3Tag::parse(&tag).ok(),
+            TagKind::Id3v1 => self.id3v1 = Id3v1Tag::parse(&tag),
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Mp3Decoder<R> {
+    /// Reads the ID3v1 tag at the end of the stream without disturbing decoding.
+    ///
+    /// The tag is also returned by `id3v1` afterwards.
+    pub fn read_id3v1(&mut self) -> Result<Option<&Id3v1Tag>, Error> {
+        self.id3v1 = id3::read_id3v1(self.reader.get_mut())?;
+        Ok(self.id3v1.as_ref())
+    }
+
+    /// Returns the total number of samples per channel in the stream.
+    ///
+    /// The length is read from the Xing, Info, or VBRI header if there is one.
+    /// Otherwise, constant bitrate streams are estimated from the size of the
+    /// stream, and other streams are measured by scanning every frame header.
+    /// When gapless playback is enabled, the encoder delay and padding are not
+    /// counted if they are known.
+    ///
+    /// The stream is assumed to start at the beginning of the reader.
+    /// Decoding is not affected.
+    pub fn total_samples(&mut self) -> Result<Length<u64>, Error> {
+        self.find_length().map(|(length, _)| length)
+    }
+
+    /// Returns the total duration of the stream.
+    ///
+    /// See `total_samples` for how the length is determined.
+    pub fn duration(&mut self) -> Result<Length<Duration>, Error> {
+        let (length, sample_rate) = self.find_length()?;
+        let hz = u64::from(sample_rate.hz());
+        Ok(length.map(|samples| {
+            Duration::from_secs(samples / hz)
+                + Duration::from_nanos(samples % hz * 1_000_000_000 / hz)
+        }))
+    }
+
+    /// Finds the total number of samples and the sample rate of the stream.
+    fn find_length(&mut self) -> Result<(Length<u64>, SampleRate), Error> {
+        let reader = self.reader.get_mut();
+        let pos = reader.stream_position()?;
+        reader.seek(SeekFrom::Start(0))?;
+        let result = Self::scan_length(reader, self.gapless);
+        reader.seek(SeekFrom::Start(pos))?;
+        result
+    }
+
+    fn scan_length(reader: &mut R, gapless: bool) -> Result<(Length<u64>, SampleRate), Error> {
+        /// The number of frames checked for a constant bitrate.
+        const NUM_CBR_FRAMES: u64 = 8;
+
+        let mut decoder = Mp3Decoder::new(&mut *reader);
+        let mut header = decoder.read_stream_start()?;
+        let sample_rate = header.sample_rate;
+        let samples_per_frame = header.num_samples() as u64;
+        if gapless {
+            if let (start, Some(end)) = decoder.find_gapless_range(&header) {
+                return Ok((Length::Exact(end.saturating_sub(start)), sample_rate));
+            }
+        }
+
+        let vbr_frames = match (&decoder.xing_header, &decoder.vbri_header) {
+            (
+                Some(XingHeader {
+                    frames: Some(frames),
+                    ..
+                }),
+                _,
+            ) => Some(*frames),
+            (_, Some(vbri_header)) => Some(vbri_header.frames),
+            _ => None,
+        };
+        if let Some(frames) = vbr_frames {
+            let total_samples = u64::from(frames) * samples_per_frame;
+            return Ok((Length::Exact(total_samples), sample_rate));
+        }
+
+        // Check whether the first frames have a constant bitrate.
+        let audio_start = decoder.reader.position() - header.header_size() as u64;
+        let bitrate = header.bitrate;
+        let mut num_frames = 0;
+        let mut is_cbr = true;
+        loop {
+            let next_header = match decoder.skip_frame_data(&header) {
+                Ok(()) => {
+                    num_frames += 1;
+                    decoder.find_frame_header()
+                }
+                Err(e) => Err(e),
+            };
+            header = match next_header {
+                Ok(header) => header,
+                Err(Error::IoError(ref e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    // The entire stream has been scanned.
+                    let total_samples = num_frames * samples_per_frame;
+                    return Ok((Length::Exact(total_samples), sample_rate));
+                }
+                Err(e) => return Err(e),
+            };
+            is_cbr &= header.bitrate == bitrate;
+            if is_cbr && num_frames == NUM_CBR_FRAMES {
+                break;
+            }
+        }
+
+        // Estimate the length from the size of the audio data.
+        let audio_end = find_audio_end(reader)?;
+        let audio_bits = audio_end.saturating_sub(audio_start) * 8;
+        let total_samples = audio_bits * u64::from(sample_rate.hz()) / u64::from(bitrate.bps());
+        Ok((Length::Estimated(total_samples), sample_rate))
+    }
+}
+
+/// Returns the end of the audio data in a seekable stream, before any APE,
+/// Lyrics3v2, and ID3v1 tags at the end of the stream.
+fn find_audio_end<R: Read + Seek>(reader: &mut R) -> std::io::Result<u64> {
+    let mut end = reader.seek(SeekFrom::End(0))?;
+    if id3::read_id3v1(reader)?.is_some() {
+        end -= id3::ID3V1_TAG_SIZE as u64;
+    }
+
+    // APE and Lyrics3v2 tags can come in either order, and are found by their footers.
+    let mut data = [0u8; ape::APE_HEADER_SIZE];
+    loop {
+        let len = u64::min(end, data.len() as u64) as usize;
+        reader.seek(SeekFrom::Start(end - len as u64))?;
+        reader.read_exact(&mut data[..len])?;
+        let tag_size = ape::ape_tag_size_from_footer(&data[..len])
+            .or_else(|| lyrics3::lyrics3v2_size_from_footer(&data[..len]));
+        match tag_size {
+            Some(size) if size as u64 <= end => end -= size as u64,
+            _ => return Ok(end),
+        }
+    }
+}
+
+/// Whether an error decoding a frame can be concealed.
+///
+/// CRC mismatches are left to the `CrcPolicy`.
+fn is_concealable(error: &Error) -> bool {
+    match error {
+        Error::Mp3Error(Mp3Error::CrcMismatch) => false,
+        Error::Mp3Error(_) => true,
+        Error::IoError(e) => e.kind() == std::io::ErrorKind::UnexpectedEof,
+    }
+}
+
+/// Checks that a caller-provided sample buffer can hold `len` samples.
+fn check_buffer_len(samples: &[f32], len: usize) -> Result<(), Error> {
+    if samples.len() < len {
+        return Err(Error::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Sample buffer holds {} samples, but {} are required",
+                samples.len(),
+                len
+            ),
+        )));
+    }
+    Ok(())
+}
+
+/// The details of a decoded frame, whose samples are held elsewhere.
+struct FrameInfo {
+    header: FrameHeader,
+    num_samples: usize,
+    skipped_bytes: u64,
+    concealed: bool,
 }
 
 /// A frame of MP3 data.
 ///
 /// Each frame contains a header describing the format of the data, and the decoded
-/// samples. An MP3 frame contains either 576 or 1152 samples (depending on the
+/// samples. An MP3 frame contains 384, 576 or 1152 samples (depending on the
 /// format).
 pub struct Frame {
     /// The header of this MP3 frame.
@@ -147,10 +894,18 @@ pub struct Frame {
     /// Only the first `num_samples` entries will contain valid data.
     /// For mono streams, the data will be duplicated to the left and right
     /// channels.
-    pub samples: [[f32; 1152]; 2],
+    pub samples: [[f32; MAX_FRAME_SAMPLES]; 2],
 
     /// The number of samples in the `samples` array.
-    /// This will be either 576 or 1152 samples depending on the
+    /// This will be either 384, 576 or 1152 samples depending on the
     /// format of the MP3.
     pub num_samples: usize,
+
+    /// The number of bytes of invalid data skipped between the previous frame
+    /// and this frame.
+    pub skipped_bytes: u64,
+
+    /// Whether this frame could not be decoded and was concealed according to
+    /// the decoder's `Concealment`.
+    pub concealed: bool,
 }
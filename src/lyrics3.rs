@@ -0,0 +1,140 @@
+//! Lyrics3v2 tag support.
+//!
+//! A Lyrics3v2 tag is a block of text fields between the MP3 data and the
+//! ID3v1 tag. `Mp3Decoder` reads these tags as it reaches them, and they are
+//! available from `Mp3Decoder::lyrics3`.
+
+use crate::error::{Error, Mp3Error};
+
+/// The marker at the start of a Lyrics3 tag.
+pub(crate) const LYRICS3_BEGIN: &[u8] = b"LYRICSBEGIN";
+
+/// The marker at the end of a Lyrics3v2 tag.
+const LYRICS3V2_END: &[u8] = b"LYRICS200";
+
+/// The size of a field header: a three character ID and a five digit size.
+const FIELD_HEADER_SIZE: usize = 8;
+
+/// The size of the tag footer: a six digit size and the end marker.
+const FOOTER_SIZE: usize = 6 + 9;
+
+/// The largest size that can be stored in the six digit footer.
+const MAX_TAG_SIZE: usize = 999_999;
+
+/// A parsed Lyrics3v2 tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lyrics3Tag {
+    /// The fields of the tag as pairs of a three character ID, such as `LYR`,
+    /// and its value.
+    pub fields: Vec<(String, String)>,
+}
+
+impl Lyrics3Tag {
+    /// Parses a Lyrics3v2 tag, from `LYRICSBEGIN` through `LYRICS200`.
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let footer_pos = match lyrics3v2_footer_pos(data) {
+            Some(pos) if data.starts_with(LYRICS3_BEGIN) => pos,
+            _ => {
+                return Err(Error::Mp3Error(Mp3Error::InvalidData(
+                    "Invalid Lyrics3v2 tag",
+                )))
+            }
+        };
+
+        let mut fields = Vec::new();
+        let mut pos = LYRICS3_BEGIN.len();
+        while pos < footer_pos {
+            let value = read_field_header(&data[pos..footer_pos])
+                .and_then(|size| data[pos + FIELD_HEADER_SIZE..footer_pos].get(..size))
+                .ok_or(Error::Mp3Error(Mp3Error::InvalidData(
+                    "Invalid Lyrics3v2 field",
+                )))?;
+            fields.push((decode_text(&data[pos..pos + 3]), decode_text(value)));
+            pos += FIELD_HEADER_SIZE + value.len();
+        }
+        Ok(Lyrics3Tag { fields })
+    }
+
+    /// Returns the value of the field with the given ID.
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field_id, _)| field_id == id)
+            .map(|(_, value)| &value[..])
+    }
+}
+
+/// Returns the total size of the Lyrics3v2 tag at the start of `data`.
+///
+/// Returns `Some(None)` if `data` does not start with a Lyrics3v2 tag, or `None`
+/// if more data is needed. `finished` marks that no more data is available.
+pub(crate) fn lyrics3v2_tag_size(data: &[u8], finished: bool) -> Option<Option<usize>> {
+    let mut pos = LYRICS3_BEGIN.len();
+    loop {
+        let end = pos + FOOTER_SIZE;
+        if data.len() < end && !finished {
+            return None;
+        }
+        let data = &data[..usize::min(end, data.len())];
+        if !data.starts_with(LYRICS3_BEGIN) {
+            return Some(None);
+        }
+        if lyrics3v2_footer_pos(data) == Some(pos) {
+            return Some(Some(end));
+        }
+        match read_field_header(&data[usize::min(pos, data.len())..]) {
+            Some(size) if pos <= MAX_TAG_SIZE => pos += FIELD_HEADER_SIZE + size,
+            _ => return Some(None),
+        }
+    }
+}
+
+/// Returns the position of the footer if `data` ends with a valid footer
+/// whose size matches its position.
+fn lyrics3v2_footer_pos(data: &[u8]) -> Option<usize> {
+    let pos = data.len().checked_sub(FOOTER_SIZE)?;
+    if data.ends_with(LYRICS3V2_END) && read_digits(&data[pos..pos + 6]) == Some(pos) {
+        Some(pos)
+    } else {
+        None
+    }
+}
+
+/// Returns the total size of the Lyrics3v2 tag whose footer is at the end of
+/// `data`.
+pub(crate) fn lyrics3v2_size_from_footer(data: &[u8]) -> Option<usize> {
+    let pos = data.len().checked_sub(FOOTER_SIZE)?;
+    if !data.ends_with(LYRICS3V2_END) {
+        return None;
+    }
+    let size = read_digits(&data[pos..pos + 6]).filter(|&size| size >= LYRICS3_BEGIN.len())?;
+    Some(size + FOOTER_SIZE)
+}
+
+/// Reads the header of a field, returning the size of its value.
+fn read_field_header(data: &[u8]) -> Option<usize> {
+    if data.len() < FIELD_HEADER_SIZE
+        || !data[..3]
+            .iter()
+            .all(|&c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    {
+        return None;
+    }
+    read_digits(&data[3..FIELD_HEADER_SIZE])
+}
+
+/// Reads a decimal number stored as ASCII digits.
+fn read_digits(data: &[u8]) -> Option<usize> {
+    data.iter().try_fold(0, |n, &c| {
+        if c.is_ascii_digit() {
+            Some(n * 10 + usize::from(c - b'0'))
+        } else {
+            None
+        }
+    })
+}
+
+/// Decodes ISO-8859-1 text.
+fn decode_text(data: &[u8]) -> String {
+    data.iter().map(|&b| char::from(b)).collect()
+}
@@ -0,0 +1,286 @@
+use crate::decoder;
+use crate::error::{Error, Mp3Error};
+use crate::sync::{FrameSync, SyncPoint};
+use crate::types::{CrcPolicy, DecoderState, FrameHeader, FrameSamples, MpegLayer};
+use crate::{ape, id3, vbr, Frame};
+
+/// The most data that is examined at once while searching for a frame or tag.
+///
+/// Data beyond this is treated as if the stream had ended, so that invalid data
+/// does not hold up decoding. This is enough for the largest Lyrics3v2 tag.
+const MAX_LOOKAHEAD: usize = 2 << 20;
+
+/// Decodes MP3 data that is pushed into the decoder as it arrives.
+///
+/// Unlike `Mp3Decoder`, this never blocks on a reader. Data is passed to `feed`
+/// in chunks of any size, and `decode` returns frames once enough data is
+/// available. Partial frames and the bit reservoir are kept between calls.
+///
+/// Frames are synced and validated in the same way as `Mp3Decoder`. Trailing
+/// APE, Lyrics3v2, and ID3v1 tags are skipped without being kept, and the
+/// first frame is skipped if it holds a VBR header. Unlike `Mp3Decoder`, this
+/// does not apply `Concealment` to invalid frames, or trim the encoder delay
+/// and padding in gapless mode.
+///
+/// Errors only affect the frame that caused them, so decoding can continue
+/// with the next call to `decode`. This is common when joining a stream part
+/// way through, as the first frames refer to data in the bit reservoir that
+/// was never received.
+///
+/// ```
+/// use puremp3::PushDecoder;
+///
+/// # fn receive() -> Option<Vec<u8>> { None }
+/// fn decode_frames(decoder: &mut PushDecoder) {
+///     loop {
+///         match decoder.decode() {
+///             Ok(Some(frame)) => {
+///                 // Operate on the frame here
+///             }
+///             Ok(None) => break,
+///             Err(e) => eprintln!("Skipped a frame: {}", e),
+///         }
+///     }
+/// }
+///
+/// let mut decoder = PushDecoder::new();
+/// while let Some(chunk) = receive() {
+///     decoder.feed(&chunk);
+///     decode_frames(&mut decoder);
+/// }
+///
+/// // Decode the frames remaining at the end of the stream.
+/// decoder.finish();
+/// decode_frames(&mut decoder);
+/// ```
+pub struct PushDecoder {
+    buffer: Vec<u8>,
+    pos: usize,
+    skip_len: usize,
+    wanted_len: Option<usize>,
+    state: DecoderState,
+    started: bool,
+    finished: bool,
+    sync: FrameSync,
+    skipped_bytes: u64,
+    frame_skipped_bytes: u64,
+}
+
+impl PushDecoder {
+    /// Creates a new `PushDecoder` with no data.
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            pos: 0,
+            skip_len: 0,
+            wanted_len: None,
+            state: DecoderState::new(),
+            started: false,
+            finished: false,
+            sync: FrameSync::new(),
+            skipped_bytes: 0,
+            frame_skipped_bytes: 0,
+        }
+    }
+
+    /// Returns how frames with invalid CRCs are handled.
+    pub fn crc_policy(&self) -> CrcPolicy {
+        self.state.crc_policy
+    }
+
+    /// Sets how frames with invalid CRCs are handled.
+    pub fn set_crc_policy(&mut self, policy: CrcPolicy) {
+        self.state.crc_policy = policy;
+    }
+
+    /// Returns the number of consecutive frames that must be found before
+    /// syncing to a stream.
+    ///
+    /// See `Mp3Decoder::sync_frames`.
+    pub fn sync_frames(&self) -> usize {
+        self.sync.sync_frames
+    }
+
+    /// Sets the number of consecutive frames that must be found before syncing
+    /// to a stream.
+    pub fn set_sync_frames(&mut self, frames: usize) {
+        self.sync.sync_frames = frames;
+    }
+
+    /// Returns the total number of bytes of invalid data that have been skipped.
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped_bytes
+    }
+
+    /// Returns the number of bytes that have been fed but not yet decoded.
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len() - self.pos
+    }
+
+    /// Adds data to the end of the stream.
+    pub fn feed(&mut self, data: &[u8]) {
+        if self.pos > 0 {
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+        }
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Marks the end of the stream.
+    ///
+    /// The frames remaining at the end of the stream can then be decoded,
+    /// as they are no longer waiting for the following frames to be validated.
+    /// A final frame that was cut short is decoded from the data that is
+    /// present, or its error is returned by `decode`.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Decodes the next MP3 `Frame` in the stream.
+    ///
+    /// Returns `None` if more data is needed to decode the next frame, or if
+    /// the end of the stream has been reached after calling `finish`.
+    /// Invalid data is skipped, and errors decoding a frame are returned. The
+    /// frame is then skipped, so decoding can continue with the next call. When
+    /// joining a stream part way through, the first frames may return
+    /// `Mp3Error::ReservoirUnderflow`.
+    pub fn decode(&mut self) -> Result<Option<Frame>, Error> {
+        self.wanted_len = None;
+        loop {
+            let header = match self.find_frame_header()? {
+                Some(header) => header,
+                None => return Ok(None),
+            };
+            let start = self.pos + header.header_size();
+            let mut end = self.pos + header.header_size() + header.data_size;
+            if end > self.buffer.len() {
+                if !self.finished {
+                    // Wait for the rest of the frame, and enough of the next
+                    // one to tell whether it is a tag.
+                    self.wanted_len = Some(end - self.buffer.len() + ape::APE_HEADER_SIZE);
+                    return Ok(None);
+                }
+                // The stream ends part way through the frame.
+                end = self.buffer.len();
+            }
+            self.pos = end;
+            self.sync.sync_header = Some(header.clone());
+
+            let data = &self.buffer[start..end];
+            if !self.started {
+                self.started = true;
+                // The frame containing the VBR header holds no audio.
+                if is_vbr_header(&header, data) {
+                    continue;
+                }
+            }
+
+            let skipped_bytes = self.skipped_bytes - self.frame_skipped_bytes;
+            self.frame_skipped_bytes = self.skipped_bytes;
+            let mut samples = [[0f32; 1152]; 2];
+            let mut out_samples = FrameSamples::planar(&mut samples);
+            let (num_samples, concealed) =
+                match decoder::process_frame(&mut self.state, data, &header, &mut out_samples) {
+                    Ok(num_samples) => (num_samples, false),
+                    Err(Error::Mp3Error(Mp3Error::CrcMismatch))
+                        if self.state.crc_policy == CrcPolicy::Conceal =>
+                    {
+                        decoder::conceal_frame(&mut self.state, &header, &mut out_samples);
+                        (header.num_samples(), true)
+                    }
+                    Err(e) => return Err(e),
+                };
+            return Ok(Some(Frame {
+                header,
+                samples,
+                num_samples,
+                skipped_bytes,
+                concealed,
+            }));
+        }
+    }
+
+    /// Returns the number of bytes that the last call to `decode` was waiting
+    /// for, if it is known.
+    ///
+    /// This is known while waiting for the rest of a frame or a tag, but not
+    /// while searching for a frame header. It can be used to size reads, so
+    /// that each frame is decoded as soon as it has arrived.
+    pub fn wanted_len(&self) -> Option<usize> {
+        self.wanted_len
+    }
+
+    /// Skips the rest of a tag, and any ID3v2 tags at the start of the stream.
+    ///
+    /// Returns `false` if more data is needed.
+    fn skip_tags(&mut self) -> bool {
+        loop {
+            let len = usize::min(self.skip_len, self.buffered_len());
+            self.pos += len;
+            self.skip_len -= len;
+            if self.skip_len > 0 {
+                self.wanted_len = Some(self.skip_len);
+                return false;
+            }
+            if self.started {
+                return true;
+            }
+
+            let data = &self.buffer[self.pos..];
+            if data.len() < id3::ID3V2_HEADER_SIZE && !self.finished {
+                return false;
+            }
+            match id3::id3v2_tag_size(data) {
+                Some(size) => self.skip_len = size,
+                None => return true,
+            }
+        }
+    }
+
+    /// Searches for a valid frame header, without consuming it.
+    /// Invalid data and any APE, Lyrics3v2, or ID3v1 tags are skipped.
+    ///
+    /// Returns `None` if more data is needed.
+    fn find_frame_header(&mut self) -> Result<Option<FrameHeader>, Error> {
+        loop {
+            if !self.skip_tags() {
+                return Ok(None);
+            }
+
+            let data = &self.buffer[self.pos..];
+            let finished = self.finished || data.len() >= MAX_LOOKAHEAD;
+            let sync_point = match self
+                .sync
+                .next(data, finished, &mut self.state.free_format_size)
+            {
+                Some(Ok(sync_point)) => sync_point,
+                // A frame header truncated by the end of the stream.
+                None | Some(Err(Error::IoError(_))) => return Ok(None),
+                Some(Err(e)) => return Err(e),
+            };
+            match sync_point {
+                SyncPoint::Frame(header) => return Ok(Some(header)),
+                SyncPoint::Tag(_, size) => self.skip_len = size,
+                SyncPoint::Invalid => {
+                    // Continue the search at the next byte.
+                    self.pos += 1;
+                    self.sync.sync_header = None;
+                    self.skipped_bytes += 1;
+                }
+            }
+        }
+    }
+}
+
+impl Default for PushDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether a frame contains a Xing or VBRI header.
+fn is_vbr_header(header: &FrameHeader, data: &[u8]) -> bool {
+    header.layer == MpegLayer::Layer3
+        && (vbr::read_xing_header(header, data).is_some()
+            || vbr::read_vbri_header(header, data).is_some())
+}
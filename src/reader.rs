@@ -0,0 +1,130 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// The amount of data that is read into the buffer at once while peeking.
+const PEEK_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The amount of data that is first peeked by `PeekReader::scan`.
+const SCAN_SIZE: usize = 512;
+
+/// A reader that can look ahead into the stream without consuming data.
+pub struct PeekReader<R> {
+    inner: R,
+    buffer: Vec<u8>,
+    pos: usize,
+    position: u64,
+}
+
+impl<R: Read> PeekReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+            pos: 0,
+            position: 0,
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Returns the number of bytes that have been consumed from the stream.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns up to `len` upcoming bytes without consuming them.
+    ///
+    /// Fewer than `len` bytes are returned if the end of the stream is reached.
+    /// The buffer grows as data is read, so `len` may exceed the data present.
+    ///
+    /// Data is read in chunks, and consumed data is only removed from the
+    /// buffer when more must be read, so peeking after each consumed byte is cheap.
+    pub fn peek(&mut self, len: usize) -> io::Result<&[u8]> {
+        if self.buffer.len() - self.pos < len && self.pos > 0 {
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+        }
+
+        while self.buffer.len() < len {
+            let start = self.buffer.len();
+            self.buffer.resize(start + PEEK_CHUNK_SIZE, 0);
+            match self.inner.read(&mut self.buffer[start..]) {
+                Ok(0) => {
+                    self.buffer.truncate(start);
+                    break;
+                }
+                Ok(n) => self.buffer.truncate(start + n),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => self.buffer.truncate(start),
+                Err(e) => {
+                    self.buffer.truncate(start);
+                    return Err(e);
+                }
+            }
+        }
+
+        let end = usize::min(self.pos + len, self.buffer.len());
+        Ok(&self.buffer[self.pos..end])
+    }
+
+    /// Examines the upcoming data with `f` without consuming it.
+    ///
+    /// `f` is passed the peeked data, and whether the end of the stream has
+    /// been reached. It returns `None` if it needs more data, in which case
+    /// more is peeked and `f` is called again.
+    pub fn scan<T>(&mut self, mut f: impl FnMut(&[u8], bool) -> Option<T>) -> io::Result<T> {
+        let mut len = SCAN_SIZE;
+        loop {
+            let data = self.peek(len)?;
+            let finished = data.len() < len;
+            match f(data, finished) {
+                Some(result) => return Ok(result),
+                None if finished => return Err(io::ErrorKind::UnexpectedEof.into()),
+                None => len *= 2,
+            }
+        }
+    }
+
+    /// Reads from the peeked data first, then from the inner reader.
+    fn read_buffered(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos < self.buffer.len() {
+            let len = usize::min(buf.len(), self.buffer.len() - self.pos);
+            buf[..len].copy_from_slice(&self.buffer[self.pos..self.pos + len]);
+            self.pos += len;
+            if self.pos == self.buffer.len() {
+                self.buffer.clear();
+                self.pos = 0;
+            }
+            Ok(len)
+        } else {
+            self.inner.read(buf)
+        }
+    }
+}
+
+impl<R: Read + Seek> PeekReader<R> {
+    /// Seeks to an absolute position in the stream, discarding any peeked data.
+    pub fn seek_to(&mut self, position: u64) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(position))?;
+        self.buffer.clear();
+        self.pos = 0;
+        self.position = position;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for PeekReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.read_buffered(buf)?;
+        self.position += len as u64;
+        Ok(len)
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::{Error, Mp3Error};
 use crate::tables::SCALE_FACTOR_BAND_INDICES;
 use crate::types::{BlockType, FrameHeader, GranuleChannelSideInfo, MainDataChannel};
 
@@ -5,7 +6,7 @@ pub fn requantize(
     header: &FrameHeader,
     side_info: &GranuleChannelSideInfo,
     main_data: &mut MainDataChannel,
-) {
+) -> Result<(), Error> {
     if side_info.block_type == BlockType::Short || side_info.block_type == BlockType::Mixed {
         if side_info.block_type == BlockType::Mixed {
             let mut sfb = 0;
@@ -27,7 +28,9 @@ pub fn requantize(
             let mut i = 36;
             while i < main_data.count1 {
                 if i == next_sfb {
-                    assert!(sfb < 14);
+                    if sfb >= 12 {
+                        return Err(invalid_band());
+                    }
                     sfb += 1;
                     next_sfb = SCALE_FACTOR_BAND_INDICES[header.sample_rate_table].1[sfb + 1] * 3;
                     window_len = SCALE_FACTOR_BAND_INDICES[header.sample_rate_table].1[sfb + 1]
@@ -58,7 +61,9 @@ pub fn requantize(
             let mut i = 0;
             while i < main_data.count1 {
                 if i == next_sfb {
-                    assert!(sfb < 14);
+                    if sfb >= 12 {
+                        return Err(invalid_band());
+                    }
                     sfb += 1;
                     next_sfb = SCALE_FACTOR_BAND_INDICES[header.sample_rate_table].1[sfb + 1] * 3;
                     window_len = SCALE_FACTOR_BAND_INDICES[header.sample_rate_table].1[sfb + 1]
@@ -87,7 +92,9 @@ pub fn requantize(
 
         for i in 0..main_data.count1 {
             if i == next_sfb {
-                assert!(sfb < 23);
+                if sfb >= 21 {
+                    return Err(invalid_band());
+                }
                 sfb += 1;
                 next_sfb = SCALE_FACTOR_BAND_INDICES[header.sample_rate_table].0[sfb + 1];
             }
@@ -95,6 +102,12 @@ pub fn requantize(
             requantize_long(side_info, i as usize, sfb, main_data);
         }
     }
+
+    Ok(())
+}
+
+fn invalid_band() -> Error {
+    Error::Mp3Error(Mp3Error::InvalidData("Invalid scale factor band"))
 }
 
 // Requnaitze subband using long blocks.
@@ -109,7 +122,6 @@ fn requantize_long(
         3.0, 3.0, 2.0, 0.0,
     ];
 
-    assert!(pos < 576);
     let sf_mult = if side_info.scalefac_scale { 1.0 } else { 0.5 };
     let pf_x_pt = if side_info.preflag { PRE_TAB[sfb] } else { 0.0 };
     let tmp1 = f64::powf(
@@ -135,7 +147,6 @@ fn requantize_short(
     subblock_gain: &[f32],
     data: &mut MainDataChannel,
 ) {
-    assert!(pos < 576);
     let sf_mult = if side_info.scalefac_scale { 1.0 } else { 0.5 };
     let tmp1 = f64::powf(2.0, -sf_mult * f64::from(data.scalefac_s[sfb][window]));
     let tmp2 = f64::powf(
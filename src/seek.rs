@@ -0,0 +1,608 @@
+//! Seeking within seekable streams.
+use crate::decoder;
+use crate::error::{Error, Mp3Error};
+use crate::types::{BitRate, FrameHeader, FrameSamples, Length, SampleRate, XingHeader};
+use crate::Mp3Decoder;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+/// The number of frames before the target frame that are decoded to restore
+/// the overlap state of the synthesis filterbanks.
+const PREROLL_FRAMES: usize = 2;
+
+/// Identifies a serialized `FrameIndex`.
+const INDEX_MAGIC: &[u8; 4] = b"MP3I";
+
+/// The version of the serialized `FrameIndex` format.
+const INDEX_VERSION: u8 = 1;
+
+/// The position of an audio frame within a stream.
+#[derive(Debug, Clone)]
+pub struct IndexedFrame {
+    /// The byte offset of the frame header from the start of the stream.
+    pub offset: u64,
+
+    /// The number of samples per channel in the stream before this frame.
+    ///
+    /// This does not account for the encoder delay.
+    pub sample: u64,
+
+    /// The number of bytes of main data taken from the bit reservoir of
+    /// preceding frames. Always 0 for Layer I and II frames.
+    pub main_data_begin: u16,
+
+    /// The header of the frame.
+    pub header: FrameHeader,
+}
+
+impl IndexedFrame {
+    /// The offset of the first byte after this frame.
+    fn end(&self) -> u64 {
+        self.offset + (self.header.header_size() + self.header.data_size) as u64
+    }
+
+    /// The number of bytes this frame adds to the bit reservoir.
+    fn main_data_size(&self) -> usize {
+        self.header
+            .data_size
+            .saturating_sub(self.header.side_data_len())
+    }
+}
+
+/// An index of the audio frames in a stream, used for exact seeking.
+///
+/// The index is built by scanning the frame headers of the stream once.
+/// It can be serialized with `to_bytes` and loaded again with `from_bytes`
+/// to avoid scanning the same stream twice.
+#[derive(Debug, Clone, Default)]
+pub struct FrameIndex {
+    frames: Vec<IndexedFrame>,
+}
+
+impl FrameIndex {
+    /// Builds an index by scanning an entire stream from its start.
+    pub fn build<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut decoder = Mp3Decoder::new(reader);
+        let header = match decoder.read_stream_start() {
+            Ok(header) => header,
+            Err(Error::IoError(ref e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                return Ok(Default::default())
+            }
+            Err(e) => return Err(e),
+        };
+        Self::scan(&mut decoder, header)
+    }
+
+    /// Scans the frames of a stream, starting from the header of the first audio frame.
+    fn scan<R: Read>(decoder: &mut Mp3Decoder<R>, mut header: FrameHeader) -> Result<Self, Error> {
+        let mut frames = Vec::new();
+        let mut sample = 0;
+        loop {
+            let offset = decoder.reader.position() - header.header_size() as u64;
+            let side_info = decoder.reader.peek(header.side_data_len())?;
+            let main_data_begin = decoder::read_main_data_begin(&header, side_info);
+            let next_header = decoder.skip_frame_data(&header).and_then(|_| {
+                let num_samples = header.num_samples() as u64;
+                frames.push(IndexedFrame {
+                    offset,
+                    sample,
+                    main_data_begin,
+                    header,
+                });
+                sample += num_samples;
+                decoder.find_frame_header()
+            });
+            header = match next_header {
+                Ok(header) => header,
+                Err(Error::IoError(ref e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                    return Ok(FrameIndex { frames });
+                }
+                Err(e) => return Err(e),
+            };
+        }
+    }
+
+    /// The frames of the stream, in order.
+    pub fn frames(&self) -> &[IndexedFrame] {
+        &self.frames
+    }
+
+    /// The total number of samples per channel in the stream.
+    ///
+    /// This does not account for the encoder delay and padding.
+    pub fn total_samples(&self) -> u64 {
+        self.frames
+            .last()
+            .map_or(0, |frame| frame.sample + frame.header.num_samples() as u64)
+    }
+
+    /// Returns the index of the frame containing the given sample, if the
+    /// sample is within the stream.
+    pub fn find_frame(&self, sample: u64) -> Option<usize> {
+        let i = self
+            .frames
+            .partition_point(|frame| frame.sample + frame.header.num_samples() as u64 <= sample);
+        if i < self.frames.len() {
+            Some(i)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the frame whose header starts at the given byte offset.
+    fn frame_at_offset(&self, offset: u64) -> Option<&IndexedFrame> {
+        let i = self
+            .frames
+            .binary_search_by_key(&offset, |frame| frame.offset)
+            .ok()?;
+        Some(&self.frames[i])
+    }
+
+    /// Serializes the index to a compact binary form.
+    ///
+    /// Byte offsets are stored relative to the end of the previous frame, and
+    /// repeated headers are stored only once.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = INDEX_MAGIC.to_vec();
+        data.push(INDEX_VERSION);
+        write_varint(&mut data, self.frames.len() as u64);
+
+        let mut prev: Option<&IndexedFrame> = None;
+        for frame in &self.frames {
+            let header = &frame.header;
+            let gap = frame.offset - prev.map_or(0, IndexedFrame::end);
+            let same_header = prev.is_some_and(|prev| {
+                prev.header.header_bytes == header.header_bytes
+                    && prev.header.data_size == header.data_size
+            });
+            write_varint(&mut data, gap << 1 | u64::from(same_header));
+            if !same_header {
+                data.extend_from_slice(&header.header_bytes);
+                if let BitRate::Free(_) = header.bitrate {
+                    write_varint(&mut data, free_format_size(header) as u64);
+                }
+            }
+            if let Some(crc) = header.crc_value {
+                data.extend_from_slice(&crc.to_be_bytes());
+            }
+            write_varint(&mut data, u64::from(frame.main_data_begin));
+            prev = Some(frame);
+        }
+        data
+    }
+
+    /// Loads an index serialized by `to_bytes`.
+    pub fn from_bytes(mut data: &[u8]) -> Result<Self, Error> {
+        let invalid = || Error::Mp3Error(Mp3Error::InvalidData("Invalid frame index"));
+        if data.len() < 5 || &data[..4] != INDEX_MAGIC || data[4] != INDEX_VERSION {
+            return Err(invalid());
+        }
+        data = &data[5..];
+
+        let num_frames = read_varint(&mut data).ok_or_else(invalid)?;
+        let mut frames: Vec<IndexedFrame> = Vec::new();
+        for _ in 0..num_frames {
+            let prev = frames.last();
+            let flags = read_varint(&mut data).ok_or_else(invalid)?;
+            let offset = prev
+                .map_or(0, IndexedFrame::end)
+                .checked_add(flags >> 1)
+                .ok_or_else(invalid)?;
+            let mut header = match prev {
+                Some(prev) if flags & 1 != 0 => prev.header.clone(),
+                _ => {
+                    let bytes = data.get(..4).ok_or_else(invalid)?;
+                    let mut header = decoder::read_frame_header(&[bytes, &[0, 0]].concat()[..])?;
+                    data = &data[4..];
+                    if header.bitrate == BitRate::Free(0) {
+                        let frame_size = read_varint(&mut data).ok_or_else(invalid)? as usize;
+                        if !header.is_valid_free_format_size(frame_size)
+                            || frame_size > decoder::MAX_FREE_FORMAT_FRAME_SIZE
+                        {
+                            return Err(invalid());
+                        }
+                        header.set_free_format_size(frame_size);
+                    }
+                    header
+                }
+            };
+            if header.crc {
+                let crc = data.get(..2).ok_or_else(invalid)?;
+                header.crc_value = Some(u16::from_be_bytes([crc[0], crc[1]]));
+                data = &data[2..];
+            }
+            let main_data_begin = read_varint(&mut data)
+                .filter(|&n| n <= u64::from(u16::MAX))
+                .ok_or_else(invalid)? as u16;
+            let sample = prev.map_or(0, |prev| prev.sample + prev.header.num_samples() as u64);
+            frames.push(IndexedFrame {
+                offset,
+                sample,
+                main_data_begin,
+                header,
+            });
+        }
+
+        if !data.is_empty() {
+            return Err(invalid());
+        }
+        Ok(FrameIndex { frames })
+    }
+}
+
+/// Returns the size of a free format frame in bytes, excluding padding.
+fn free_format_size(header: &FrameHeader) -> usize {
+    let padding_size = if header.padding {
+        header.padding_size()
+    } else {
+        0
+    };
+    header.header_size() + header.data_size - padding_size
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn read_varint(data: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = data.split_first()?;
+        *data = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+impl<R: Read + Seek> Mp3Decoder<R> {
+    /// Seeks to the given sample, counted per channel from the start of the stream.
+    ///
+    /// The next `Frame` returned by `next_frame` starts exactly at this sample.
+    /// When gapless playback is enabled, sample positions do not include the
+    /// encoder delay. Seeking past the end of the stream moves to the end.
+    ///
+    /// The frame containing the sample is found using the decoder's
+    /// `FrameIndex`, which is built on the first seek. The preceding frames are
+    /// decoded to restore the bit reservoir and the filterbank state. The
+    /// stream is assumed to start at the beginning of the reader.
+    pub fn seek_to_sample(&mut self, sample: u64) -> Result<(), Error> {
+        self.seek(|_| sample)
+    }
+
+    /// Seeks to the given time from the start of the stream.
+    ///
+    /// See `seek_to_sample` for details.
+    pub fn seek_to_time(&mut self, time: Duration) -> Result<(), Error> {
+        self.seek(|sample_rate| time_to_sample(time, sample_rate))
+    }
+
+    /// Seeks close to the given sample without scanning the stream, returning
+    /// the sample that was actually reached.
+    ///
+    /// The byte position of the sample is estimated from the seek table of the
+    /// Xing or VBRI header, or from the bit rate of the first frame if there is
+    /// no seek table. Decoding resumes at a frame boundary shortly after that
+    /// position, once enough frames have been read to restore the bit reservoir
+    /// and the filterbank state.
+    ///
+    /// The returned position is `Length::Exact` if the decoder has a
+    /// `FrameIndex`, such as one set with `set_frame_index`, in which the frame
+    /// that was reached is looked up. Otherwise, it is estimated from the seek
+    /// table as well, and returned as `Length::Estimated`. Use `seek_to_sample`
+    /// for exact seeking.
+    pub fn seek_to_sample_approximate(&mut self, sample: u64) -> Result<Length<u64>, Error> {
+        self.seek_approximate(|_| sample)
+    }
+
+    /// Seeks close to the given time without scanning the stream, returning
+    /// the sample that was actually reached.
+    ///
+    /// See `seek_to_sample_approximate` for details.
+    pub fn seek_to_time_approximate(&mut self, time: Duration) -> Result<Length<u64>, Error> {
+        self.seek_approximate(|sample_rate| time_to_sample(time, sample_rate))
+    }
+
+    /// Returns the frame index of the stream, scanning the stream if the
+    /// index has not been built or set yet.
+    ///
+    /// The position of the stream is unchanged.
+    pub fn frame_index(&mut self) -> Result<&FrameIndex, Error> {
+        let index = match self.frame_index.take() {
+            Some(index) => index,
+            None => {
+                let reader = self.reader.get_mut();
+                let pos = reader.stream_position()?;
+                reader.seek(SeekFrom::Start(0))?;
+                let index = FrameIndex::build(&mut *reader);
+                reader.seek(SeekFrom::Start(pos))?;
+                index?
+            }
+        };
+        Ok(self.frame_index.insert(index))
+    }
+
+    /// Sets the frame index used for seeking, such as one loaded with
+    /// `FrameIndex::from_bytes`. The index must belong to this stream.
+    pub fn set_frame_index(&mut self, index: FrameIndex) {
+        self.frame_index = Some(index);
+    }
+
+    fn seek(&mut self, to_sample: impl FnOnce(SampleRate) -> u64) -> Result<(), Error> {
+        if !self.started {
+            // Read the tags and VBR header at the start of the stream.
+            self.read_stream_start()?;
+        }
+
+        self.frame_index()?;
+        let index = self.frame_index.take().unwrap_or_default();
+        let result = match index.frames.first() {
+            Some(frame) => {
+                let mut target = to_sample(frame.header.sample_rate);
+                if self.gapless {
+                    target += self.gapless_range(&frame.header).0;
+                }
+                self.seek_to_frame(&index, target)
+            }
+            None => Ok(()),
+        };
+        self.frame_index = Some(index);
+        result
+    }
+
+    fn seek_approximate(
+        &mut self,
+        to_sample: impl FnOnce(SampleRate) -> u64,
+    ) -> Result<Length<u64>, Error> {
+        if !self.started {
+            self.read_stream_start()?;
+        }
+
+        self.reader.seek_to(self.audio_start)?;
+        let header = self.find_frame_header()?;
+        let samples_per_frame = header.num_samples() as u64;
+        let delay = if self.gapless {
+            self.gapless_range(&header).0
+        } else {
+            0
+        };
+        let target = to_sample(header.sample_rate) + delay;
+        let seek_points = self.seek_points(&header);
+        let byte_points: Vec<_> = seek_points.iter().map(|&(x, y)| (y, x)).collect();
+        let offset_to_sample = |offset| {
+            let sample = interpolate(&byte_points, offset);
+            (sample + samples_per_frame / 2) / samples_per_frame * samples_per_frame
+        };
+
+        // Aim a few frames early to leave room for the preroll frames.
+        let preroll_samples = (PREROLL_FRAMES as u64 + 1) * samples_per_frame;
+        let frame_start = target / samples_per_frame * samples_per_frame;
+        let offset = interpolate(&seek_points, frame_start.saturating_sub(preroll_samples));
+        self.reset_decoding();
+        if frame_start < preroll_samples || offset <= self.audio_start {
+            // Decoding from the start of the stream needs no preroll.
+            self.reader.seek_to(self.audio_start)?;
+            self.decoded_samples = 0;
+            self.seek_target = target;
+            return Ok(Length::Exact(target - delay));
+        }
+        self.reader.seek_to(offset)?;
+        self.sync.sync_header = None;
+
+        // Resync to the next frame, then read frames until the bit reservoir is
+        // filled and the preroll frames have been decoded.
+        let mut samples = [[0f32; 1152]; 2];
+        let mut samples = FrameSamples::planar(&mut samples);
+        let mut next_sample = None;
+        let mut first_frame = None;
+        let mut num_decoded = 0;
+        let sample = loop {
+            let header = match self.find_frame_header() {
+                Ok(header) => header,
+                Err(Error::IoError(ref e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                    break next_sample.unwrap_or_else(|| offset_to_sample(self.reader.position()));
+                }
+                Err(e) => return Err(e),
+            };
+            let frame_offset = self.reader.position() - header.header_size() as u64;
+            let sample = next_sample.unwrap_or_else(|| offset_to_sample(frame_offset));
+            first_frame.get_or_insert((frame_offset, sample));
+            if num_decoded == PREROLL_FRAMES {
+                self.reader.seek_to(frame_offset)?;
+                break sample;
+            }
+
+            let side_info = self.reader.peek(header.side_data_len())?;
+            let main_data_begin = decoder::read_main_data_begin(&header, side_info);
+            let result = if usize::from(main_data_begin) <= self.state.frame_buffer_len {
+                num_decoded += 1;
+                decoder::process_frame(&mut self.state, &mut self.reader, &header, &mut samples)
+                    .map(|_| ())
+            } else {
+                decoder::skip_frame(&mut self.state, &mut self.reader, &header)
+            };
+            match result {
+                Ok(()) | Err(Error::Mp3Error(_)) => (),
+                Err(Error::IoError(ref e)) if e.kind() == ErrorKind::UnexpectedEof => break sample,
+                Err(e) => return Err(e),
+            }
+            next_sample = Some(sample + header.num_samples() as u64);
+        };
+
+        // The frame that was reached is exactly positioned by the frame index,
+        // counting on from the first frame after resyncing.
+        let sample = match (&self.frame_index, first_frame) {
+            (Some(index), Some((offset, estimate))) => index
+                .frame_at_offset(offset)
+                .map(|frame| Length::Exact(frame.sample + (sample - estimate))),
+            (Some(index), None) => Some(Length::Exact(index.total_samples())),
+            (None, _) => None,
+        }
+        .unwrap_or(Length::Estimated(sample));
+
+        // Samples before the target are dropped if the estimate lands early.
+        self.decoded_samples = sample.value();
+        self.seek_target = u64::max(sample.value(), target);
+        Ok(sample.map(|sample| u64::max(sample, target) - delay))
+    }
+
+    /// Returns points mapping sample positions to byte offsets in the stream,
+    /// for interpolation with `interpolate`.
+    ///
+    /// The points are taken from the seek table of the Xing or VBRI header.
+    /// Otherwise, the stream is assumed to have the bit rate of `header`.
+    fn seek_points(&self, header: &FrameHeader) -> Vec<(u64, u64)> {
+        let samples_per_frame = header.num_samples() as u64;
+        if let Some(XingHeader {
+            frames: Some(frames),
+            bytes: Some(bytes),
+            toc: Some(toc),
+            ..
+        }) = &self.xing_header
+        {
+            // The seek table is relative to the start of the frame containing the header.
+            let total_samples = u64::from(*frames) * samples_per_frame;
+            return (0..=100)
+                .map(|i| {
+                    let position = toc.get(i).map_or(256, |&position| u64::from(position));
+                    let offset = self.stream_start + u64::from(*bytes) * position / 256;
+                    (total_samples * i as u64 / 100, offset)
+                })
+                .collect();
+        }
+
+        if let Some(vbri_header) = self
+            .vbri_header
+            .as_ref()
+            .filter(|vbri| !vbri.toc.is_empty())
+        {
+            let samples_per_entry = u64::from(vbri_header.frames_per_entry) * samples_per_frame;
+            let mut point = (0, self.audio_start);
+            let mut points = vec![point];
+            for &entry in &vbri_header.toc {
+                point.0 += samples_per_entry;
+                point.1 += u64::from(entry) * u64::from(vbri_header.toc_scale);
+                points.push(point);
+            }
+            return points;
+        }
+
+        // One second of audio takes `bps / 8` bytes.
+        let sample_rate = u64::from(header.sample_rate.hz());
+        let bps = u64::from(header.bitrate.bps());
+        vec![
+            (0, self.audio_start),
+            (8 * sample_rate, self.audio_start + bps),
+        ]
+    }
+
+    /// Clears the decoder state before decoding from a new position.
+    fn reset_decoding(&mut self) {
+        self.state.reset();
+        self.last_granule.1 = 0;
+        self.fade_in = false;
+    }
+
+    /// Seeks to the frame containing `target`, decoding the preceding frames
+    /// as needed to restore the decoder state.
+    fn seek_to_frame(&mut self, index: &FrameIndex, target: u64) -> Result<(), Error> {
+        self.reset_decoding();
+        self.seek_target = target;
+        let target_frame = match index.find_frame(target) {
+            Some(i) => i,
+            None => {
+                // The target is past the end of the stream, so seek to the end.
+                let end = index.frames.last().map_or(0, IndexedFrame::end);
+                self.reader.seek_to(end)?;
+                self.decoded_samples = index.total_samples();
+                return Ok(());
+            }
+        };
+
+        let frames = &index.frames;
+        let mut samples = [[0f32; 1152]; 2];
+        let mut samples = FrameSamples::planar(&mut samples);
+        for (i, frame) in frames
+            .iter()
+            .enumerate()
+            .take(target_frame)
+            .skip(preroll_start(frames, target_frame))
+        {
+            self.reader
+                .seek_to(frame.offset + frame.header.header_size() as u64)?;
+            if i + PREROLL_FRAMES >= target_frame {
+                let header = &frame.header;
+                match decoder::process_frame(
+                    &mut self.state,
+                    &mut self.reader,
+                    header,
+                    &mut samples,
+                ) {
+                    Ok(_) | Err(Error::Mp3Error(_)) => (),
+                    Err(e) => return Err(e),
+                }
+            } else {
+                decoder::skip_frame(&mut self.state, &mut self.reader, &frame.header)?;
+            }
+        }
+
+        let frame = &frames[target_frame];
+        self.reader.seek_to(frame.offset)?;
+        self.decoded_samples = frame.sample;
+        Ok(())
+    }
+}
+
+/// Converts a time to a sample position, rounding down.
+fn time_to_sample(time: Duration, sample_rate: SampleRate) -> u64 {
+    (time.as_nanos() * u128::from(sample_rate.hz()) / 1_000_000_000) as u64
+}
+
+/// Linearly interpolates `x` between the two surrounding `points`, which must
+/// be sorted by their first value. Values outside of the points are
+/// extrapolated from the first or last two points.
+fn interpolate(points: &[(u64, u64)], x: u64) -> u64 {
+    let i = points
+        .partition_point(|&(point_x, _)| point_x <= x)
+        .clamp(1, points.len() - 1);
+    let (x0, y0) = points[i - 1];
+    let (x1, y1) = points[i];
+    if x1 <= x0 {
+        return y0;
+    }
+
+    let (x, x0, x1, y0, y1) = (x as i128, x0 as i128, x1 as i128, y0 as i128, y1 as i128);
+    let y = y0 + (x - x0) * (y1 - y0) / (x1 - x0);
+    y.clamp(0, u64::MAX.into()) as u64
+}
+
+/// Finds the first frame that must be read to decode the target frame and
+/// the preroll frames before it.
+///
+/// Each decoded frame needs `main_data_begin` bytes of main data from the
+/// frames before it in the bit reservoir.
+fn preroll_start(frames: &[IndexedFrame], target_frame: usize) -> usize {
+    let decode_start = target_frame.saturating_sub(PREROLL_FRAMES);
+    let mut start = decode_start;
+    for (i, frame) in frames
+        .iter()
+        .enumerate()
+        .take(target_frame + 1)
+        .skip(decode_start)
+    {
+        let mut available = 0;
+        let mut first = i;
+        while first > 0 && available < usize::from(frame.main_data_begin) {
+            first -= 1;
+            available += frames[first].main_data_size();
+        }
+        start = usize::min(start, first);
+    }
+    start
+}
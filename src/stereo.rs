@@ -90,7 +90,7 @@ fn stereo_instensity_long(
 
     let sfb_start = band_indices.0[sfb] as usize;
     let sfb_end = band_indices.0[sfb + 1] as usize;
-    if pos != 7 {
+    if is_valid_intensity_position(header, pos) {
         if header.version != MpegVersion::Mpeg1 {
             let i = (sfb >> 1) << (scalefac_compress as usize & 1);
             if sfb & 1 == 0 {
@@ -133,7 +133,7 @@ fn stereo_instensity_short(
         let is_pos = main_data.channels[0].scalefac_s[sfb][win] as usize;
         let sfb_start = band_indices.1[sfb] as usize * 3 + window_len * win;
         let sfb_end = sfb_start + window_len;
-        if is_pos != 7 {
+        if is_valid_intensity_position(header, is_pos) {
             if header.version != MpegVersion::Mpeg1 {
                 let i = (sfb >> 1) << (scalefac_compress as usize & 1);
                 if sfb & 1 == 0 {
@@ -160,3 +160,14 @@ fn stereo_instensity_short(
         }
     }
 }
+
+/// Returns whether an intensity stereo position is valid.
+///
+/// Position 7 is illegal, and MPEG-1 streams only use positions 0 to 6.
+fn is_valid_intensity_position(header: &FrameHeader, pos: usize) -> bool {
+    if header.version == MpegVersion::Mpeg1 {
+        pos < 7
+    } else {
+        pos != 7
+    }
+}
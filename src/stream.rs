@@ -0,0 +1,109 @@
+use crate::decoder::MAX_FREE_FORMAT_FRAME_SIZE;
+use crate::error::Error;
+use crate::{Frame, PushDecoder};
+use futures_core::Stream;
+use futures_io::AsyncRead;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Decodes MP3 streams from an `AsyncRead`, yielding a `Stream` of `Frame`s.
+///
+/// Only available with the `async` feature. Decoding is done by a
+/// `PushDecoder`, which is fed as data is read. Tokio readers can be used
+/// through the `compat` adapters of the `tokio-util` crate.
+///
+/// Once in sync, each read is sized to the rest of the next frame, along with
+/// the start of the following frame, so frames are yielded as soon as they
+/// have been read. While searching for the first frame, data is read in
+/// larger chunks, as several frames are needed to sync to the stream.
+///
+/// Errors are yielded in place of the frames that could not be decoded, and
+/// the stream ends at the end of the reader.
+pub struct AsyncMp3Decoder<R> {
+    reader: R,
+    decoder: PushDecoder,
+    buffer: Box<[u8]>,
+    finished: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncMp3Decoder<R> {
+    /// Creates a new `AsyncMp3Decoder` from the given reader.
+    pub fn new(reader: R) -> Self {
+        Self::with_decoder(reader, PushDecoder::new())
+    }
+
+    /// Creates a new `AsyncMp3Decoder` that feeds the data from the reader into
+    /// the given `PushDecoder`.
+    pub fn with_decoder(reader: R, decoder: PushDecoder) -> Self {
+        Self {
+            reader,
+            decoder,
+            // Each read fits the largest frame.
+            buffer: vec![0; MAX_FREE_FORMAT_FRAME_SIZE].into_boxed_slice(),
+            finished: false,
+        }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Reading from the reader will disturb decoding.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Gets a reference to the `PushDecoder` used for decoding.
+    pub fn decoder(&self) -> &PushDecoder {
+        &self.decoder
+    }
+
+    /// Gets a mutable reference to the `PushDecoder` used for decoding, to
+    /// change its options.
+    pub fn decoder_mut(&mut self) -> &mut PushDecoder {
+        &mut self.decoder
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for AsyncMp3Decoder<R> {
+    type Item = Result<Frame, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.decoder.decode() {
+                Ok(Some(frame)) => return Poll::Ready(Some(Ok(frame))),
+                Ok(None) if this.finished => return Poll::Ready(None),
+                Ok(None) => (),
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
+
+            let len = this
+                .decoder
+                .wanted_len()
+                .map_or(this.buffer.len(), |len| usize::min(len, this.buffer.len()));
+            let buffer = &mut this.buffer[..len];
+            let len = match Pin::new(&mut this.reader).poll_read(cx, buffer) {
+                Poll::Ready(Ok(len)) => len,
+                Poll::Ready(Err(ref e)) if e.kind() == ErrorKind::Interrupted => continue,
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(Error::IoError(e)))),
+                Poll::Pending => return Poll::Pending,
+            };
+            if len == 0 {
+                this.finished = true;
+                this.decoder.finish();
+            } else {
+                this.decoder.feed(&this.buffer[..len]);
+            }
+        }
+    }
+}
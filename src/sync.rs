@@ -0,0 +1,215 @@
+//! Finding frames and tags in a stream of MP3 data.
+//!
+//! This is shared by `Mp3Decoder` and `PushDecoder`. The stream is examined
+//! from buffered data, and `None` is returned when more data is needed to
+//! decide. `finished` marks that no more data is available, such as at the end
+//! of the stream.
+
+use crate::decoder::{self, MAX_FREE_FORMAT_FRAME_SIZE};
+use crate::error::{Error, Mp3Error};
+use crate::types::{BitRate, FrameHeader};
+use crate::{ape, id3, lyrics3};
+
+/// A kind of tag found after the MP3 data.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum TagKind {
+    Ape,
+    Lyrics3,
+    Id3v1,
+}
+
+/// What was found at the current position in the stream.
+pub(crate) enum SyncPoint {
+    /// A frame header that is in sync with the stream.
+    Frame(FrameHeader),
+
+    /// A tag of the given size in bytes.
+    Tag(TagKind, usize),
+
+    /// Invalid data. The search continues at the next byte.
+    Invalid,
+}
+
+/// Keeps a decoder in sync with the frames of a stream.
+pub(crate) struct FrameSync {
+    /// The number of consecutive frames that must be found before syncing.
+    pub sync_frames: usize,
+
+    /// The header of the previous frame, while in sync.
+    pub sync_header: Option<FrameHeader>,
+}
+
+impl FrameSync {
+    pub fn new() -> Self {
+        Self {
+            sync_frames: 3,
+            sync_header: None,
+        }
+    }
+
+    /// Examines the stream at the start of `data`.
+    ///
+    /// The frame size of free format streams is measured once, and kept in
+    /// `free_format_size`.
+    pub fn next(
+        &self,
+        data: &[u8],
+        finished: bool,
+        free_format_size: &mut Option<usize>,
+    ) -> Option<Result<SyncPoint, Error>> {
+        if let Some((kind, size)) = find_tag(data, finished)? {
+            return Some(Ok(SyncPoint::Tag(kind, size)));
+        }
+
+        let header = match read_frame_header(data, finished, free_format_size)? {
+            Ok(header) => header,
+            Err(Error::Mp3Error(Mp3Error::InvalidData(_))) => return Some(Ok(SyncPoint::Invalid)),
+            Err(e) => return Some(Err(e)),
+        };
+        if self.check_sync(data, finished, &header, *free_format_size)? {
+            Some(Ok(SyncPoint::Frame(header)))
+        } else {
+            Some(Ok(SyncPoint::Invalid))
+        }
+    }
+
+    /// Checks whether the frame header at the start of `data` is in sync with the stream.
+    ///
+    /// If the decoder is already in sync, the header must match the previous frame.
+    /// Otherwise, it must be followed by `sync_frames - 1` matching frames.
+    fn check_sync(
+        &self,
+        data: &[u8],
+        finished: bool,
+        header: &FrameHeader,
+        free_format_size: Option<usize>,
+    ) -> Option<bool> {
+        if let Some(ref sync_header) = self.sync_header {
+            if header.matches(sync_header) {
+                return Some(true);
+            }
+        }
+
+        let mut prev_header = header.clone();
+        let mut offset = header.header_size() + header.data_size;
+        for _ in 1..self.sync_frames {
+            let next = data.get(offset..).unwrap_or(&[]);
+            if next.len() < ape::APE_HEADER_SIZE && !finished {
+                return None;
+            }
+            if next.is_empty() {
+                // The stream ends after the frame.
+                return Some(offset == data.len());
+            }
+            if next.starts_with(b"TAG")
+                || next.starts_with(b"APETAGEX")
+                || next.starts_with(lyrics3::LYRICS3_BEGIN)
+            {
+                return Some(true);
+            }
+
+            let mut next_header = match decoder::read_frame_header(next) {
+                Ok(next_header) if next_header.matches(&prev_header) => next_header,
+                Ok(_) | Err(Error::Mp3Error(_)) => {
+                    // The stream may end with an APE tag without a header.
+                    let tag_size = ape::ape_footer_tag_size(next, finished)?;
+                    return Some(tag_size.is_some());
+                }
+                // A header truncated by the end of the stream.
+                Err(Error::IoError(_)) => return Some(true),
+            };
+            if let BitRate::Free(_) = next_header.bitrate {
+                match free_format_size {
+                    Some(frame_size) if next_header.is_valid_free_format_size(frame_size) => {
+                        next_header.set_free_format_size(frame_size)
+                    }
+                    Some(_) => return Some(false),
+                    None => return Some(true),
+                }
+            }
+            offset += next_header.header_size() + next_header.data_size;
+            prev_header = next_header;
+        }
+        Some(true)
+    }
+}
+
+/// Finds a tag that follows the MP3 data at the start of `data`, returning its
+/// kind and size.
+///
+/// An ID3v1 tag ends the stream, so a `TAG` block is only treated as a tag
+/// when it is exactly the last 128 bytes.
+fn find_tag(data: &[u8], finished: bool) -> Option<Option<(TagKind, usize)>> {
+    if data.len() < ape::APE_HEADER_SIZE && !finished {
+        return None;
+    }
+
+    if let Some(ape_header) = ape::read_ape_header(data) {
+        let size = if ape_header.is_header() {
+            ape::APE_HEADER_SIZE + ape_header.size as usize
+        } else {
+            // A tag without items, or the footer of a tag whose items could
+            // not be walked.
+            ape::APE_HEADER_SIZE
+        };
+        Some(Some((TagKind::Ape, size)))
+    } else if data.starts_with(lyrics3::LYRICS3_BEGIN) {
+        let size = lyrics3::lyrics3v2_tag_size(data, finished)?;
+        Some(size.map(|size| (TagKind::Lyrics3, size)))
+    } else if data.starts_with(b"TAG") {
+        if data.len() <= id3::ID3V1_TAG_SIZE && !finished {
+            return None;
+        }
+        if data.len() == id3::ID3V1_TAG_SIZE {
+            Some(Some((TagKind::Id3v1, id3::ID3V1_TAG_SIZE)))
+        } else {
+            Some(None)
+        }
+    } else {
+        let size = ape::ape_footer_tag_size(data, finished)?;
+        Some(size.map(|size| (TagKind::Ape, size)))
+    }
+}
+
+/// Parses the frame header at the start of `data`, measuring the frame size of
+/// free format frames.
+fn read_frame_header(
+    data: &[u8],
+    finished: bool,
+    free_format_size: &mut Option<usize>,
+) -> Option<Result<FrameHeader, Error>> {
+    let mut header = match decoder::read_frame_header(data) {
+        Ok(header) => header,
+        Err(Error::IoError(_)) if !finished => return None,
+        Err(e) => return Some(Err(e)),
+    };
+
+    if let BitRate::Free(_) = header.bitrate {
+        // Free format streams have a constant frame size, so it only needs
+        // to be measured once.
+        let frame_size = match *free_format_size {
+            Some(frame_size) => frame_size,
+            None => match decoder::measure_free_format_frame(data, &header) {
+                Ok(frame_size) => {
+                    *free_format_size = Some(frame_size);
+                    frame_size
+                }
+                // The next frame may not have arrived yet.
+                Err(_)
+                    if !finished
+                        && data.len() < header.header_size() + MAX_FREE_FORMAT_FRAME_SIZE =>
+                {
+                    return None
+                }
+                Err(e) => return Some(Err(e)),
+            },
+        };
+        if !header.is_valid_free_format_size(frame_size) {
+            return Some(Err(Error::Mp3Error(Mp3Error::InvalidData(
+                "Free format frame too small",
+            ))));
+        }
+        header.set_free_format_size(frame_size);
+    }
+    Some(Ok(header))
+}
@@ -1,5 +1,5 @@
 use crate::tables::{COS_N12, COS_N36, IMDCT_WIN, SBS_N_WIN, SYNTH_DTBL};
-use crate::types::{BlockType, GranuleChannelSideInfo};
+use crate::types::{BlockType, FrameSamples, GranuleChannelSideInfo};
 
 #[allow(clippy::unreadable_literal)]
 pub fn antialias(side_info: &GranuleChannelSideInfo, samples: &mut [f32; 576]) {
@@ -94,47 +94,64 @@ pub fn frequency_inversion(samples: &mut [f32; 576]) {
     }
 }
 
-pub fn subband_synthesis(samples: &[f32; 576], v_vec: &mut [f32; 1024], out: &mut [f32]) {
+/// Runs the polyphase filterbank on a granule, writing 576 samples to channel
+/// `ch` of `out`, starting at sample `pos`.
+pub fn subband_synthesis(
+    samples: &[f32; 576],
+    v_vec: &mut [f32; 1024],
+    out: &mut FrameSamples,
+    ch: usize,
+    pos: usize,
+) {
     let mut s_vec = [0f32; 32];
-    let mut u_vec = [0f32; 512];
 
     for ss in 0..18 {
-        for i in (64..=1023).rev() {
-            v_vec[i] = v_vec[i - 64];
-        }
-
         for i in 0..32 {
             s_vec[i] = samples[i * 18 + ss];
         }
 
-        for (i, row) in SBS_N_WIN.iter().enumerate() {
-            let mut sum = 0.0;
-            for (j, &sbs_n_win) in row.iter().enumerate() {
-                sum += sbs_n_win * s_vec[j];
-            }
-            v_vec[i] = sum;
-        }
+        out.write(ch, pos + 32 * ss, &polyphase_synthesis(&s_vec, v_vec));
+    }
+}
 
-        for i in 0..8 {
-            for j in 0..32 {
-                let i6 = i << 6;
-                let i7 = i << 7;
+/// Runs the polyphase filterbank on a single time slot of 32 subband samples,
+/// producing 32 output samples.
+pub fn polyphase_synthesis(s_vec: &[f32; 32], v_vec: &mut [f32; 1024]) -> [f32; 32] {
+    let mut u_vec = [0f32; 512];
 
-                u_vec[i6 + j] = v_vec[i7 + j];
-                u_vec[i6 + j + 32] = v_vec[i7 + j + 96];
-            }
+    for i in (64..=1023).rev() {
+        v_vec[i] = v_vec[i - 64];
+    }
+
+    for (i, row) in SBS_N_WIN.iter().enumerate() {
+        let mut sum = 0.0;
+        for (j, &sbs_n_win) in row.iter().enumerate() {
+            sum += sbs_n_win * s_vec[j];
         }
+        v_vec[i] = sum;
+    }
 
-        for i in 0..512 {
-            u_vec[i] *= SYNTH_DTBL[i];
+    for i in 0..8 {
+        for j in 0..32 {
+            let i6 = i << 6;
+            let i7 = i << 7;
+
+            u_vec[i6 + j] = v_vec[i7 + j];
+            u_vec[i6 + j + 32] = v_vec[i7 + j + 96];
         }
+    }
 
-        for i in 0..32 {
-            let mut sum = 0.0;
-            for j in 0..16 {
-                sum += u_vec[(j << 5) + i];
-            }
-            out[(32 * ss) + i] = sum;
+    for i in 0..512 {
+        u_vec[i] *= SYNTH_DTBL[i];
+    }
+
+    let mut out = [0f32; 32];
+    for (i, out) in out.iter_mut().enumerate() {
+        let mut sum = 0.0;
+        for j in 0..16 {
+            sum += u_vec[(j << 5) + i];
         }
+        *out = sum;
     }
+    out
 }
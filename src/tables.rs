@@ -4208,3 +4208,235 @@ pub const IMDCT_WIN: [[f32; 36]; 4] = [
         0.043619387,
     ],
 ];
+
+/// Layer I and II scale factors, indexed by the 6-bit scalefactor index.
+#[allow(clippy::unreadable_literal)]
+#[allow(clippy::excessive_precision)]
+pub const LAYER12_SCALE_FACTORS: [f32; 64] = [
+    2.00000000000000,
+    1.58740105196820,
+    1.25992104989487,
+    1.00000000000000,
+    0.79370052598410,
+    0.62996052494744,
+    0.50000000000000,
+    0.39685026299205,
+    0.31498026247372,
+    0.25000000000000,
+    0.19842513149602,
+    0.15749013123686,
+    0.12500000000000,
+    0.09921256574801,
+    0.07874506561843,
+    0.06250000000000,
+    0.04960628287401,
+    0.03937253280921,
+    0.03125000000000,
+    0.02480314143700,
+    0.01968626640461,
+    0.01562500000000,
+    0.01240157071850,
+    0.00984313320230,
+    0.00781250000000,
+    0.00620078535925,
+    0.00492156660115,
+    0.00390625000000,
+    0.00310039267963,
+    0.00246078330058,
+    0.00195312500000,
+    0.00155019633981,
+    0.00123039165029,
+    0.00097656250000,
+    0.00077509816991,
+    0.00061519582514,
+    0.00048828125000,
+    0.00038754908495,
+    0.00030759791257,
+    0.00024414062500,
+    0.00019377454248,
+    0.00015379895629,
+    0.00012207031250,
+    0.00009688727124,
+    0.00007689947814,
+    0.00006103515625,
+    0.00004844363562,
+    0.00003844973907,
+    0.00003051757812,
+    0.00002422181781,
+    0.00001922486954,
+    0.00001525878906,
+    0.00001211090890,
+    0.00000961243477,
+    0.00000762939453,
+    0.00000605545445,
+    0.00000480621738,
+    0.00000381469727,
+    0.00000302772723,
+    0.00000240310869,
+    0.00000190734863,
+    0.00000151386361,
+    0.00000120155435,
+    0.00000095367432,
+];
+
+/// Layer II quantization classes: (number of steps, grouped, bits per codeword).
+pub const LAYER2_QUANT_CLASSES: [(u32, bool, u32); 17] = [
+    (3, true, 5),
+    (5, true, 7),
+    (7, false, 3),
+    (9, true, 10),
+    (15, false, 4),
+    (31, false, 5),
+    (63, false, 6),
+    (127, false, 7),
+    (255, false, 8),
+    (511, false, 9),
+    (1023, false, 10),
+    (2047, false, 11),
+    (4095, false, 12),
+    (8191, false, 13),
+    (16383, false, 14),
+    (32767, false, 15),
+    (65535, false, 16),
+];
+
+// Quantization classes for each allocation value, starting at 1.
+const LAYER2_ALLOC_4A: &[u8] = &[0, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+const LAYER2_ALLOC_4B: &[u8] = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 16];
+const LAYER2_ALLOC_4C: &[u8] = &[0, 1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+const LAYER2_ALLOC_4D: &[u8] = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14];
+const LAYER2_ALLOC_3A: &[u8] = &[0, 1, 2, 3, 4, 5, 16];
+const LAYER2_ALLOC_3B: &[u8] = &[0, 1, 3, 4, 5, 6, 7];
+const LAYER2_ALLOC_2A: &[u8] = &[0, 1, 16];
+const LAYER2_ALLOC_2B: &[u8] = &[0, 1, 3];
+
+// Layer II bit allocation tables (ISO/IEC 11172-3 Table B.2 and ISO/IEC 13818-3 Table B.1).
+// Each entry is the number of allocation bits for a subband and the quantization
+// classes of its allocation values.
+
+// Table B.2a: 48 kHz, or 56-80 kbps per channel.
+pub const LAYER2_ALLOCATION_A: [(u32, &[u8]); 27] = [
+    (4, LAYER2_ALLOC_4A),
+    (4, LAYER2_ALLOC_4A),
+    (4, LAYER2_ALLOC_4A),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (2, LAYER2_ALLOC_2A),
+    (2, LAYER2_ALLOC_2A),
+    (2, LAYER2_ALLOC_2A),
+    (2, LAYER2_ALLOC_2A),
+];
+
+// Table B.2b: 44.1/32 kHz, 96 kbps or higher per channel.
+pub const LAYER2_ALLOCATION_B: [(u32, &[u8]); 30] = [
+    (4, LAYER2_ALLOC_4A),
+    (4, LAYER2_ALLOC_4A),
+    (4, LAYER2_ALLOC_4A),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (4, LAYER2_ALLOC_4B),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (3, LAYER2_ALLOC_3A),
+    (2, LAYER2_ALLOC_2A),
+    (2, LAYER2_ALLOC_2A),
+    (2, LAYER2_ALLOC_2A),
+    (2, LAYER2_ALLOC_2A),
+    (2, LAYER2_ALLOC_2A),
+    (2, LAYER2_ALLOC_2A),
+    (2, LAYER2_ALLOC_2A),
+];
+
+// Table B.2c: 44.1/48 kHz, 48 kbps or lower per channel.
+pub const LAYER2_ALLOCATION_C: [(u32, &[u8]); 8] = [
+    (4, LAYER2_ALLOC_4C),
+    (4, LAYER2_ALLOC_4C),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+];
+
+// Table B.2d: 32 kHz, 48 kbps or lower per channel.
+pub const LAYER2_ALLOCATION_D: [(u32, &[u8]); 12] = [
+    (4, LAYER2_ALLOC_4C),
+    (4, LAYER2_ALLOC_4C),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+];
+
+// MPEG-2 LSF.
+pub const LAYER2_ALLOCATION_LSF: [(u32, &[u8]); 30] = [
+    (4, LAYER2_ALLOC_4D),
+    (4, LAYER2_ALLOC_4D),
+    (4, LAYER2_ALLOC_4D),
+    (4, LAYER2_ALLOC_4D),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (3, LAYER2_ALLOC_3B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+    (2, LAYER2_ALLOC_2B),
+];
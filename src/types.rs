@@ -1,5 +1,7 @@
 //! Types and data structures used by the MP3 decoder.
 
+use crate::MAX_FRAME_SAMPLES;
+
 /// The maximum number of channels supported in an MP3.
 pub const MAX_CHANNELS: usize = 2;
 
@@ -17,13 +19,14 @@ pub struct FrameHeader {
     pub version: MpegVersion,
 
     /// The MPEG layer of the frame.
-    ///
-    /// Currently only MPEG Layer III is supported.
     pub layer: MpegLayer,
 
     /// Whether the frame contains a CRC checksum.
     pub crc: bool,
 
+    /// The CRC checksum read from the frame, if the frame contains one.
+    pub crc_value: Option<u16>,
+
     /// The bitrate of this frame.
     pub bitrate: BitRate,
 
@@ -45,6 +48,7 @@ pub struct FrameHeader {
     /// The emphasis of this frame.
     pub emphasis: Emphasis,
 
+    pub(crate) header_bytes: [u8; 4],
     pub(crate) sample_rate_table: usize,
     pub(crate) data_size: usize,
 }
@@ -61,10 +65,90 @@ impl FrameHeader {
                     17
                 }
             }
-            _ => unimplemented!(),
+            // Layers I and II have no side information.
+            MpegLayer::Layer1 | MpegLayer::Layer2 => 0,
+        }
+    }
+
+    /// The number of samples per channel in this frame.
+    pub(crate) fn num_samples(&self) -> usize {
+        match self.layer {
+            MpegLayer::Layer1 => 384,
+            MpegLayer::Layer2 => 1152,
+            MpegLayer::Layer3 => self.num_granules() * 576,
+        }
+    }
+
+    /// The first subband that is coded as intensity stereo in Layers I and II.
+    ///
+    /// In these layers, the mode extension bits select the bound instead
+    /// of the intensity/mid-side stereo flags.
+    pub(crate) fn joint_stereo_bound(&self) -> usize {
+        match self.channels {
+            Channels::JointStereo {
+                intensity_stereo,
+                mid_side_stereo,
+            } => 4 * (1 + usize::from(intensity_stereo) + 2 * usize::from(mid_side_stereo)),
+            _ => 32,
+        }
+    }
+
+    /// Relates the bit rate and sample rate to the frame size in bytes.
+    pub(crate) fn frame_size_factor(&self) -> u32 {
+        match (self.layer, self.version) {
+            (MpegLayer::Layer1, _) => 48,
+            (MpegLayer::Layer3, MpegVersion::Mpeg2) | (MpegLayer::Layer3, MpegVersion::Mpeg2_5) => {
+                72
+            }
+            _ => 144,
         }
     }
 
+    /// The size of the padding slot in bytes.
+    pub(crate) fn padding_size(&self) -> usize {
+        match self.layer {
+            MpegLayer::Layer1 => 4,
+            _ => 1,
+        }
+    }
+
+    /// The size of the frame header in bytes, including the CRC.
+    pub(crate) fn header_size(&self) -> usize {
+        if self.crc {
+            6
+        } else {
+            4
+        }
+    }
+
+    /// Whether two frames can belong to the same stream.
+    pub(crate) fn matches(&self, other: &FrameHeader) -> bool {
+        self.version == other.version
+            && self.layer == other.layer
+            && self.sample_rate == other.sample_rate
+    }
+
+    /// Sets the size of this frame in bytes, excluding padding.
+    pub(crate) fn set_frame_size(&mut self, frame_size: usize) {
+        let padding_size = if self.padding { self.padding_size() } else { 0 };
+        self.data_size = frame_size + padding_size - self.header_size();
+    }
+
+    /// Whether a free format frame of the given size, excluding padding, is
+    /// large enough to hold this frame's header and side info.
+    pub(crate) fn is_valid_free_format_size(&self, frame_size: usize) -> bool {
+        frame_size >= self.header_size() + self.side_data_len()
+    }
+
+    /// Sets the size of a free format frame as measured from the stream,
+    /// excluding padding. The bit rate is calculated from the frame size.
+    pub(crate) fn set_free_format_size(&mut self, frame_size: usize) {
+        let bps = frame_size as u64 * u64::from(self.sample_rate.hz())
+            / u64::from(self.frame_size_factor());
+        self.bitrate = BitRate::Free(bps as u32);
+        self.set_frame_size(frame_size);
+    }
+
     pub(crate) fn num_granules(&self) -> usize {
         if self.version == MpegVersion::Mpeg1 {
             2
@@ -74,15 +158,13 @@ impl FrameHeader {
     }
 
     pub(crate) fn is_intensity_stereo(&self) -> bool {
-        if let Channels::JointStereo {
-            intensity_stereo: true,
-            ..
-        } = self.channels
-        {
-            true
-        } else {
-            false
-        }
+        matches!(
+            self.channels,
+            Channels::JointStereo {
+                intensity_stereo: true,
+                ..
+            }
+        )
     }
 }
 
@@ -149,6 +231,7 @@ impl Channels {
 /// The bit rate of an MP3 stream.
 ///
 /// MP3 supports specific bitrates, depending on the MPEG version and layer.
+/// Free format streams use a constant, non-standard bit rate.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum BitRate {
     Kbps8,
@@ -165,10 +248,19 @@ pub enum BitRate {
     Kbps128,
     Kbps144,
     Kbps160,
+    Kbps176,
     Kbps192,
     Kbps224,
     Kbps256,
+    Kbps288,
     Kbps320,
+    Kbps352,
+    Kbps384,
+    Kbps416,
+    Kbps448,
+
+    /// A free format bit rate in bits per second, measured from the frame size.
+    Free(u32),
 }
 
 impl BitRate {
@@ -189,10 +281,17 @@ impl BitRate {
             BitRate::Kbps128 => 128_000,
             BitRate::Kbps144 => 144_000,
             BitRate::Kbps160 => 160_000,
+            BitRate::Kbps176 => 176_000,
             BitRate::Kbps192 => 192_000,
             BitRate::Kbps224 => 224_000,
             BitRate::Kbps256 => 256_000,
+            BitRate::Kbps288 => 288_000,
             BitRate::Kbps320 => 320_000,
+            BitRate::Kbps352 => 352_000,
+            BitRate::Kbps384 => 384_000,
+            BitRate::Kbps416 => 416_000,
+            BitRate::Kbps448 => 448_000,
+            BitRate::Free(bps) => bps,
         }
     }
 }
@@ -236,8 +335,178 @@ pub enum Emphasis {
     CcitJ17,
 }
 
+/// How the decoder handles frames that fail the CRC check.
+///
+/// Only frames that contain a CRC checksum are checked.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum CrcPolicy {
+    /// Decode the frame as usual, without checking the CRC.
+    #[default]
+    Ignore,
+
+    /// Return `Mp3Error::CrcMismatch` for the frame.
+    Error,
+
+    /// Output silence in place of the frame, and mark it by `Frame::concealed`.
+    Conceal,
+}
+
+/// How the decoder conceals frames that cannot be decoded.
+///
+/// Frames are concealed when their data is invalid or truncated. Concealed
+/// frames are marked by `Frame::concealed`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Concealment {
+    /// Return the error for the frame.
+    #[default]
+    None,
+
+    /// Output silence in place of the frame.
+    Silence,
+
+    /// Repeat the last granule of the previous frame.
+    Repeat,
+
+    /// Repeat the last granule of the previous frame while fading it out,
+    /// then fade in the following frame.
+    Fade,
+}
+
+/// The length of a stream, which is either exact or estimated.
+///
+/// Returned by `Mp3Decoder::total_samples` and `Mp3Decoder::duration`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Length<T> {
+    /// The length was read from a VBR header or counted by scanning the stream.
+    Exact(T),
+
+    /// The length was estimated from the size of the stream and its bitrate.
+    Estimated(T),
+}
+
+impl<T> Length<T> {
+    /// Returns the length, whether it is exact or estimated.
+    pub fn value(self) -> T {
+        match self {
+            Length::Exact(value) | Length::Estimated(value) => value,
+        }
+    }
+
+    /// Returns `true` if the length is exact.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Length::Exact(_))
+    }
+
+    /// Converts the length, keeping whether it is exact.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Length<U> {
+        match self {
+            Length::Exact(value) => Length::Exact(f(value)),
+            Length::Estimated(value) => Length::Estimated(f(value)),
+        }
+    }
+}
+
+/// A Xing or Info header, found in the first frame of many MP3 files.
+///
+/// VBR files use a `Xing` header, while LAME writes an `Info` header for CBR files.
+/// The frame containing the header holds no audio.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct XingHeader {
+    /// Whether this is an `Info` header rather than a `Xing` header.
+    pub is_info: bool,
+
+    /// The number of audio frames in the stream, not counting the frame containing the header.
+    pub frames: Option<u32>,
+
+    /// The size of the stream in bytes.
+    pub bytes: Option<u32>,
+
+    /// The seek table. Entry `i` is the byte position at `i` percent of the
+    /// duration, scaled so that 256 is the size of the stream.
+    pub toc: Option<[u8; 100]>,
+
+    /// The quality indicator, from 0 (best) to 100 (worst).
+    pub quality: Option<u32>,
+}
+
+/// A VBRI header, written to the first frame by Fraunhofer encoders.
+///
+/// The frame containing the header holds no audio.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VbriHeader {
+    /// The version of the header.
+    pub version: u16,
+
+    /// The encoder delay.
+    pub delay: u16,
+
+    /// The quality indicator.
+    pub quality: u16,
+
+    /// The size of the stream in bytes.
+    pub bytes: u32,
+
+    /// The number of audio frames in the stream, not counting the frame containing the header.
+    pub frames: u32,
+
+    /// The scale factor of the seek table entries.
+    pub toc_scale: u16,
+
+    /// The number of frames covered by each seek table entry.
+    pub frames_per_entry: u16,
+
+    /// The seek table. Each entry multiplied by `toc_scale` is the size in
+    /// bytes of the next `frames_per_entry` frames.
+    pub toc: Vec<u32>,
+}
+
+/// The LAME extension tag, which follows the Xing or Info header in files
+/// encoded by LAME and compatible encoders.
+///
+/// The tag is only read if its CRC is valid.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LameTag {
+    /// The encoder version string, such as `LAME3.100`.
+    pub encoder: String,
+
+    /// The revision of the tag format.
+    pub revision: u8,
+
+    /// The VBR method used by the encoder.
+    pub vbr_method: u8,
+
+    /// The lowpass filter frequency in Hz, if known.
+    pub lowpass: Option<u32>,
+
+    /// The peak signal amplitude, where 1.0 is full scale.
+    pub peak: Option<f32>,
+
+    /// The track ReplayGain adjustment in dB.
+    pub track_gain: Option<f32>,
+
+    /// The album ReplayGain adjustment in dB.
+    pub album_gain: Option<f32>,
+
+    /// The number of samples of delay added by the encoder at the start of the stream.
+    pub encoder_delay: u16,
+
+    /// The number of samples of padding added by the encoder at the end of the stream.
+    pub encoder_padding: u16,
+
+    /// The size of the stream in bytes, including the frame containing the tag.
+    pub music_length: u32,
+
+    /// The CRC-16 of the audio data.
+    pub music_crc: u16,
+
+    /// The CRC-16 of the frame up to this tag's CRC field.
+    pub crc: u16,
+}
+
 // Internal types
 pub struct DecoderState {
+    pub crc_policy: CrcPolicy,
+    pub free_format_size: Option<usize>,
     pub frame_buffer: [u8; 4096],
     pub frame_buffer_len: usize,
     pub store: [[[f32; 18]; 32]; 2],
@@ -247,12 +516,109 @@ pub struct DecoderState {
 impl DecoderState {
     pub fn new() -> Self {
         DecoderState {
+            crc_policy: CrcPolicy::default(),
+            free_format_size: None,
             frame_buffer: [0; 4096],
             frame_buffer_len: 0,
             store: [[[0f32; 18]; 32]; 2],
             sbs_v_vec: [[0f32; 1024]; 2],
         }
     }
+
+    /// Clears the bit reservoir and the synthesis state, keeping the decoder options.
+    pub fn reset(&mut self) {
+        *self = DecoderState {
+            crc_policy: self.crc_policy,
+            free_format_size: self.free_format_size,
+            ..DecoderState::new()
+        };
+    }
+}
+
+/// The buffer that the samples of a frame are decoded into.
+///
+/// Samples are written straight into the caller's buffer, which is either a
+/// slice per channel or a single slice of interleaved left and right samples.
+/// Each channel holds at least `MAX_FRAME_SAMPLES` samples.
+pub enum FrameSamples<'a> {
+    Planar([&'a mut [f32]; 2]),
+    Interleaved(&'a mut [f32]),
+}
+
+impl<'a> FrameSamples<'a> {
+    pub fn planar(samples: &'a mut [[f32; MAX_FRAME_SAMPLES]; 2]) -> Self {
+        let [left, right] = samples;
+        FrameSamples::Planar([left, right])
+    }
+
+    pub fn get(&self, ch: usize, i: usize) -> f32 {
+        match self {
+            FrameSamples::Planar(channels) => channels[ch][i],
+            FrameSamples::Interleaved(samples) => samples[2 * i + ch],
+        }
+    }
+
+    pub fn get_mut(&mut self, ch: usize, i: usize) -> &mut f32 {
+        match self {
+            FrameSamples::Planar(channels) => &mut channels[ch][i],
+            FrameSamples::Interleaved(samples) => &mut samples[2 * i + ch],
+        }
+    }
+
+    /// Writes samples to a channel, starting at sample `pos`.
+    pub fn write(&mut self, ch: usize, pos: usize, samples: &[f32]) {
+        match self {
+            FrameSamples::Planar(channels) => {
+                channels[ch][pos..pos + samples.len()].copy_from_slice(samples)
+            }
+            FrameSamples::Interleaved(out) => {
+                let out = out[2 * pos + ch..].iter_mut().step_by(2);
+                for (out, &sample) in out.zip(samples) {
+                    *out = sample;
+                }
+            }
+        }
+    }
+
+    /// Sets every sample of a frame to zero.
+    pub fn clear(&mut self) {
+        match self {
+            FrameSamples::Planar(channels) => {
+                for channel in channels.iter_mut() {
+                    channel[..MAX_FRAME_SAMPLES].fill(0.0);
+                }
+            }
+            FrameSamples::Interleaved(samples) => samples[..2 * MAX_FRAME_SAMPLES].fill(0.0),
+        }
+    }
+
+    /// Copies the left channel to the right channel, for mono frames.
+    pub fn copy_left_to_right(&mut self) {
+        match self {
+            FrameSamples::Planar([left, right]) => {
+                right[..MAX_FRAME_SAMPLES].copy_from_slice(&left[..MAX_FRAME_SAMPLES])
+            }
+            FrameSamples::Interleaved(samples) => {
+                for sample in samples[..2 * MAX_FRAME_SAMPLES].chunks_exact_mut(2) {
+                    sample[1] = sample[0];
+                }
+            }
+        }
+    }
+
+    /// Moves the samples in `range` of each channel to the start of the frame.
+    pub fn move_to_start(&mut self, range: std::ops::Range<usize>) {
+        match self {
+            FrameSamples::Planar(channels) => {
+                for channel in channels.iter_mut() {
+                    channel.copy_within(range.clone(), 0);
+                }
+            }
+            FrameSamples::Interleaved(samples) => {
+                samples.copy_within(2 * range.start..2 * range.end, 0)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -267,8 +633,9 @@ pub struct GranuleSideInfo {
     pub channels: [GranuleChannelSideInfo; 2],
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 pub enum BlockType {
+    #[default]
     Long,
     Short,
     Mixed,
@@ -276,12 +643,6 @@ pub enum BlockType {
     End,
 }
 
-impl Default for BlockType {
-    fn default() -> BlockType {
-        BlockType::Long
-    }
-}
-
 #[derive(Debug, Default)]
 pub struct GranuleChannelSideInfo {
     pub part2_3_length: u16,
@@ -0,0 +1,153 @@
+use crate::crc::crc16_arc;
+use crate::types::{FrameHeader, LameTag, VbriHeader, XingHeader};
+
+/// The size of the LAME tag.
+const LAME_TAG_SIZE: usize = 36;
+
+/// The offset of a VBRI header from the start of its frame.
+///
+/// Unlike Xing headers, VBRI headers are always 32 bytes after the 4-byte
+/// frame header, regardless of the CRC and the size of the side info.
+const VBRI_HEADER_OFFSET: usize = 36;
+
+/// The size of a VBRI header, excluding the seek table.
+const VBRI_HEADER_SIZE: usize = 26;
+
+/// Returns the offset of a VBR header within the data of a Layer III frame,
+/// which follows the side information.
+fn vbr_header_offset(header: &FrameHeader) -> usize {
+    header.side_data_len()
+}
+
+/// Reads the Xing or Info header from the data of the first frame, if any.
+///
+/// `data` is the frame data following the frame header and CRC.
+/// Also returns the offset in `data` following the Xing header.
+pub fn read_xing_header(header: &FrameHeader, frame_data: &[u8]) -> Option<(XingHeader, usize)> {
+    let mut data = frame_data.get(vbr_header_offset(header)..)?;
+    let is_info = match data.get(..4)? {
+        b"Xing" => false,
+        b"Info" => true,
+        _ => return None,
+    };
+    let flags = read_u32(data.get(4..8)?);
+    data = &data[8..];
+
+    let mut read_field = |flag: u32, len: usize| -> Option<Option<&[u8]>> {
+        if flags & flag == 0 {
+            return Some(None);
+        }
+        let field = data.get(..len)?;
+        data = &data[len..];
+        Some(Some(field))
+    };
+
+    let frames = read_field(0x1, 4)?.map(read_u32);
+    let bytes = read_field(0x2, 4)?.map(read_u32);
+    let toc = read_field(0x4, 100)?.map(|field| {
+        let mut toc = [0u8; 100];
+        toc.copy_from_slice(field);
+        toc
+    });
+    let quality = read_field(0x8, 4)?.map(read_u32);
+    let xing_header = XingHeader {
+        is_info,
+        frames,
+        bytes,
+        toc,
+        quality,
+    };
+    Some((xing_header, frame_data.len() - data.len()))
+}
+
+/// Reads the LAME tag at `offset` in the data of the first frame, if any.
+///
+/// Returns `None` if the tag is missing or its CRC is invalid.
+pub fn read_lame_tag(header: &FrameHeader, frame_data: &[u8], offset: usize) -> Option<LameTag> {
+    let data = frame_data.get(offset..offset + LAME_TAG_SIZE)?;
+    if !data[..4].iter().all(u8::is_ascii_alphanumeric) {
+        return None;
+    }
+
+    // The CRC covers the entire frame up to the CRC field, including the header.
+    let crc = u16::from_be_bytes([data[34], data[35]]);
+    let mut expected = crc16_arc(0, &header.header_bytes);
+    if let Some(crc_value) = header.crc_value {
+        expected = crc16_arc(expected, &crc_value.to_be_bytes());
+    }
+    expected = crc16_arc(expected, &frame_data[..offset + 34]);
+    if crc != expected {
+        return None;
+    }
+
+    let encoder = data[..9]
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| char::from(c))
+        .collect::<String>();
+    let peak = read_u32(&data[11..15]);
+    let delay_padding = read_u32(&data[20..24]) & 0xff_ffff;
+    Some(LameTag {
+        encoder: encoder.trim_end().to_string(),
+        revision: data[9] >> 4,
+        vbr_method: data[9] & 0xf,
+        lowpass: Some(u32::from(data[10]) * 100).filter(|&lowpass| lowpass != 0),
+        peak: Some(peak as f32 / (1 << 23) as f32).filter(|_| peak != 0),
+        track_gain: read_replay_gain(&data[15..17], 1),
+        album_gain: read_replay_gain(&data[17..19], 2),
+        encoder_delay: (delay_padding >> 12) as u16,
+        encoder_padding: (delay_padding & 0xfff) as u16,
+        music_length: read_u32(&data[28..32]),
+        music_crc: u16::from_be_bytes([data[32], data[33]]),
+        crc,
+    })
+}
+
+/// Reads the VBRI header from the data of the first frame, if any.
+///
+/// `data` is the frame data following the frame header and CRC.
+pub fn read_vbri_header(header: &FrameHeader, data: &[u8]) -> Option<VbriHeader> {
+    let data = data.get(VBRI_HEADER_OFFSET - header.header_size()..)?;
+    if data.len() < VBRI_HEADER_SIZE || !data.starts_with(b"VBRI") {
+        return None;
+    }
+
+    let read_u16 = |pos: usize| u16::from_be_bytes([data[pos], data[pos + 1]]);
+    let num_entries = usize::from(read_u16(18));
+    let entry_size = usize::from(read_u16(22));
+    if entry_size == 0 || entry_size > 4 {
+        return None;
+    }
+
+    let toc = data
+        .get(VBRI_HEADER_SIZE..VBRI_HEADER_SIZE + num_entries * entry_size)?
+        .chunks_exact(entry_size)
+        .map(|entry| entry.iter().fold(0, |n, &b| (n << 8) | u32::from(b)))
+        .collect();
+    Some(VbriHeader {
+        version: read_u16(4),
+        delay: read_u16(6),
+        quality: read_u16(8),
+        bytes: read_u32(&data[10..14]),
+        frames: read_u32(&data[14..18]),
+        toc_scale: read_u16(20),
+        frames_per_entry: read_u16(24),
+        toc,
+    })
+}
+
+/// Reads a ReplayGain field, returning the adjustment in dB.
+///
+/// The field is ignored if its name code doesn't match `name`.
+fn read_replay_gain(data: &[u8], name: u16) -> Option<f32> {
+    let field = u16::from_be_bytes([data[0], data[1]]);
+    if field >> 13 != name {
+        return None;
+    }
+    let gain = f32::from(field & 0x1ff) / 10.0;
+    Some(if field & 0x200 != 0 { -gain } else { gain })
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
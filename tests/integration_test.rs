@@ -1,4 +1,4 @@
-use puremp3::Mp3Decoder;
+use puremp3::{BitRate, CrcPolicy, Error, Frame, Mp3Decoder, Mp3Error, MpegLayer};
 
 #[test]
 fn test_decode() -> Result<(), Box<dyn std::error::Error>> {
@@ -7,3 +7,1212 @@ fn test_decode() -> Result<(), Box<dyn std::error::Error>> {
     decoder.frames().last();
     Ok(())
 }
+
+#[test]
+fn test_decode_layer1() -> Result<(), Box<dyn std::error::Error>> {
+    // MPEG-1 Layer I, 32 kbps, 44.1 kHz, mono.
+    // Only the first subband is allocated, with a constant sample value.
+    let mut frame = vec![0xff, 0xff, 0x10, 0xc0];
+    frame.extend_from_slice(&[
+        0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x0f, 0xbb, 0xbb, 0xbb, 0xbb, 0xbb, 0xb8, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ]);
+    let data = frame.repeat(4);
+
+    let frames: Vec<_> = Mp3Decoder::new(&data[..]).frames().collect();
+    assert_eq!(frames.len(), 4);
+    for frame in &frames {
+        assert_eq!(frame.header.layer, MpegLayer::Layer1);
+        assert_eq!(frame.num_samples, 384);
+    }
+    let last = &frames[3];
+    assert!(last.samples[0][..384].iter().any(|&s| s.abs() > 0.1));
+    assert!(last.samples[0][..384].iter().all(|&s| s.abs() <= 1.0));
+    assert_eq!(&last.samples[0][..384], &last.samples[1][..384]);
+    Ok(())
+}
+
+#[test]
+fn test_decode_layer2() -> Result<(), Box<dyn std::error::Error>> {
+    // MPEG-1 Layer II, 32 kbps, 44.1 kHz, mono.
+    // Only the first subband is allocated, with a constant grouped sample value.
+    let mut frame = vec![0xff, 0xfd, 0x10, 0xc0];
+    frame.extend_from_slice(&[
+        0x20, 0x00, 0x00, 0x20, 0xfe, 0x7c, 0xf9, 0xf3, 0xe7, 0xcf, 0x9f, 0x3e, 0x7c, 0xf9, 0xf0,
+    ]);
+    frame.resize(104, 0);
+    let data = frame.repeat(4);
+
+    let frames: Vec<_> = Mp3Decoder::new(&data[..]).frames().collect();
+    assert_eq!(frames.len(), 4);
+    for frame in &frames {
+        assert_eq!(frame.header.layer, MpegLayer::Layer2);
+        assert_eq!(frame.num_samples, 1152);
+    }
+    let last = &frames[3];
+    assert!(last.samples[0][..1152].iter().any(|&s| s.abs() > 0.1));
+    assert!(last.samples[0][..1152].iter().all(|&s| s.abs() <= 1.0));
+    Ok(())
+}
+
+#[test]
+fn test_free_format() -> Result<(), Box<dyn std::error::Error>> {
+    // MPEG-1 Layer I, free format, 44.1 kHz, mono, with 40 byte frames of silence.
+    let mut frame = vec![0xff, 0xff, 0x00, 0xc0];
+    frame.resize(40, 0);
+    let data = frame.repeat(3);
+
+    let frames: Vec<_> = Mp3Decoder::new(&data[..]).frames().collect();
+    assert_eq!(frames.len(), 3);
+    for frame in &frames {
+        assert_eq!(frame.header.bitrate, BitRate::Free(36_750));
+        assert_eq!(frame.header.bitrate.bps(), 36_750);
+        assert_eq!(frame.num_samples, 384);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_crc() -> Result<(), Box<dyn std::error::Error>> {
+    // MPEG-1 Layer I, 32 kbps, 44.1 kHz, mono, protected by a CRC.
+    let mut frame = vec![0xff, 0xfe, 0x10, 0xc0, 0x60, 0xce];
+    frame.extend_from_slice(&[
+        0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x0f, 0xbb, 0xbb, 0xbb, 0xbb, 0xbb, 0xb8, 0x00, 0x00, 0x00,
+    ]);
+    let mut data = frame.repeat(3);
+    // Corrupt the CRC of the second frame.
+    data[frame.len() + 5] ^= 0xff;
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.set_crc_policy(CrcPolicy::Error);
+    assert_eq!(decoder.next_frame()?.header.crc_value, Some(0x60ce));
+    assert!(matches!(
+        decoder.next_frame(),
+        Err(Error::Mp3Error(Mp3Error::CrcMismatch))
+    ));
+    assert!(decoder.next_frame().is_ok());
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.set_crc_policy(CrcPolicy::Conceal);
+    let frames: Vec<_> = decoder.frames().collect();
+    assert_eq!(frames.len(), 3);
+    assert!(frames[0].samples[0][..384].iter().any(|&s| s != 0.0));
+    let concealed: Vec<_> = frames.iter().map(|frame| frame.concealed).collect();
+    assert_eq!(concealed, [false, true, false]);
+    // The filterbank runs over the concealed frame, which starts with the
+    // decaying output of the previous frame.
+    assert!(frames[1].samples[0][..32].iter().any(|&s| s != 0.0));
+    Ok(())
+}
+
+#[test]
+fn test_id3v2() -> Result<(), Box<dyn std::error::Error>> {
+    // ID3v2.4 tag containing a false frame sync.
+    let mut tag = b"ID3\x04\x00\x00\x00\x00\x00\x14".to_vec();
+    tag.extend_from_slice(&[0xff, 0xff, 0x10, 0xc0]);
+    tag.resize(30, 0);
+
+    // MPEG-1 Layer I, 32 kbps, 44.1 kHz, mono silence.
+    let mut frame = vec![0xff, 0xff, 0x10, 0xc0];
+    frame.resize(32, 0);
+
+    let mut data = tag.clone();
+    data.extend_from_slice(&frame.repeat(2));
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    assert_eq!(decoder.id3v2(), None);
+    decoder.next_frame()?;
+    assert_eq!(decoder.id3v2(), Some(&tag[..]));
+    assert_eq!(decoder.frames().count(), 1);
+
+    // A truncated tag claiming the largest size is not buffered in full.
+    let data = b"ID3\x04\x00\x00\x7f\x7f\x7f\x7f\x00\x00".to_vec();
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    assert!(matches!(decoder.next_frame(), Err(Error::IoError(_))));
+    Ok(())
+}
+
+/// Builds an ID3v2 tag from a list of frame headers and contents.
+fn id3v2_tag(version: u8, flags: u8, body: &[u8]) -> Vec<u8> {
+    let size = body.len() as u32;
+    let mut tag = vec![b'I', b'D', b'3', version, 0, flags];
+    tag.extend((0..4).rev().map(|i| ((size >> (7 * i)) & 0x7f) as u8));
+    tag.extend_from_slice(body);
+    tag
+}
+
+#[test]
+fn test_id3v2_frames() -> Result<(), Box<dyn std::error::Error>> {
+    use puremp3::id3::{Comment, FrameContent, Id3v2Tag};
+
+    // ID3v2.3 with an extended header and unsynchronisation.
+    let mut body = vec![0, 0, 0, 6, 0, 0, 0, 0, 0, 0];
+    body.extend_from_slice(b"TIT2\x00\x00\x00\x06\x00\x00\x00Caf\xe9!");
+    body.extend_from_slice(b"TPE1\x00\x00\x00\x05\x00\x00\x01\xff\x00\xfeA\x00");
+    body.extend_from_slice(b"COMM\x00\x00\x00\x09\x00\x00\x00eng\x00note");
+    body.extend_from_slice(
+        b"APIC\x00\x00\x00\x10\x00\x00\x00image/png\x00\x03\x00\x89\xff\x00\xe0",
+    );
+    body.resize(body.len() + 8, 0);
+    let tag = Id3v2Tag::parse(&id3v2_tag(3, 0xc0, &body))?;
+    assert_eq!(tag.version, 3);
+    assert_eq!(tag.title(), Some("Café!"));
+    assert_eq!(tag.artist(), Some("A"));
+    assert_eq!(
+        tag.comments().collect::<Vec<_>>(),
+        [&Comment {
+            language: "eng".to_string(),
+            description: String::new(),
+            text: "note".to_string(),
+        }]
+    );
+    let picture = tag.pictures().next().unwrap();
+    assert_eq!(picture.mime_type, "image/png");
+    assert_eq!(picture.picture_type, 3);
+    assert_eq!(picture.data, [0x89, 0xff, 0xe0]);
+
+    // ID3v2.4 with UTF-8 and UTF-16BE text, a per-frame data length indicator and unsynchronisation.
+    let mut body = b"TALB\x00\x00\x00\x05\x00\x00\x03\xc3\xa9t\xc3".to_vec();
+    body.extend_from_slice(b"TRCK\x00\x00\x00\x09\x00\x00\x02\x003\x00\x00\x001\x002");
+    body.extend_from_slice(b"TDRC\x00\x00\x00\x05\x00\x00\x032004");
+    body.extend_from_slice(b"TXXX\x00\x00\x00\x07\x00\x00\x03key\x00ok");
+    body.extend_from_slice(b"PRIV\x00\x00\x00\x0a\x00\x03\x00\x00\x00\x02me\x00\xff\x00\x01");
+    let tag = Id3v2Tag::parse(&id3v2_tag(4, 0, &body))?;
+    assert_eq!(tag.album(), Some("ét\u{fffd}"));
+    assert_eq!(
+        tag.get("TRCK").map(|frame| &frame.content),
+        Some(&FrameContent::Text(vec!["3".to_string(), "12".to_string()]))
+    );
+    assert_eq!(tag.year(), Some("2004"));
+    assert_eq!(
+        tag.get("TXXX").map(|frame| &frame.content),
+        Some(&FrameContent::UserText {
+            description: "key".to_string(),
+            value: "ok".to_string(),
+        })
+    );
+    assert_eq!(
+        tag.get("PRIV").map(|frame| &frame.content),
+        Some(&FrameContent::Private {
+            owner: "me".to_string(),
+            data: vec![0xff, 0x01],
+        })
+    );
+
+    // ID3v2.2 frame IDs are converted to their ID3v2.3 equivalents.
+    let tag = Id3v2Tag::parse(&id3v2_tag(2, 0, b"TT2\x00\x00\x03\x00Hi"))?;
+    assert_eq!(tag.title(), Some("Hi"));
+    Ok(())
+}
+
+#[test]
+fn test_id3v1() -> Result<(), Box<dyn std::error::Error>> {
+    // ID3v1.1 tag whose title contains a false frame sync.
+    let mut tag = b"TAG".to_vec();
+    tag.extend_from_slice(&[0xff, 0xff, 0x10, 0xc0]);
+    tag.resize(33, 0);
+    tag.extend_from_slice(b"Artist");
+    tag.resize(63, b' ');
+    tag.extend_from_slice(b"Album");
+    tag.resize(93, 0);
+    tag.extend_from_slice(b"1999Comment");
+    tag.resize(126, 0);
+    tag.extend_from_slice(&[7, 17]);
+
+    // MPEG-1 Layer I, 32 kbps, 44.1 kHz, mono silence.
+    let mut frame = vec![0xff, 0xff, 0x10, 0xc0];
+    frame.resize(32, 0);
+
+    let mut data = frame.repeat(2);
+    data.extend_from_slice(&tag);
+
+    let mut decoder = Mp3Decoder::new(std::io::Cursor::new(&data[..]));
+    let id3v1 = decoder.read_id3v1()?.cloned().unwrap();
+    assert_eq!(id3v1.artist, "Artist");
+    assert_eq!(id3v1.album, "Album");
+    assert_eq!(id3v1.year, "1999");
+    assert_eq!(id3v1.comment, "Comment");
+    assert_eq!(id3v1.track, Some(7));
+    assert_eq!(id3v1.genre_name(), Some("Rock"));
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.next_frame()?;
+    decoder.next_frame()?;
+    assert_eq!(decoder.id3v1(), None);
+    assert!(decoder.next_frame().is_err());
+    assert_eq!(decoder.id3v1(), Some(&id3v1));
+    Ok(())
+}
+
+#[test]
+fn test_trailing_tags() -> Result<(), Box<dyn std::error::Error>> {
+    use puremp3::ape::{ApeTag, ApeValue};
+
+    fn ape_header(size: u32, item_count: u32, flags: u32) -> Vec<u8> {
+        let mut header = b"APETAGEX".to_vec();
+        for n in &[2000, size, item_count, flags] {
+            header.extend_from_slice(&n.to_le_bytes());
+        }
+        header.resize(32, 0);
+        header
+    }
+
+    // APEv2 items, including binary data containing a false frame sync.
+    let mut items = Vec::new();
+    items.extend_from_slice(b"\x08\x00\x00\x00\x00\x00\x00\x00REPLAYGAIN_TRACK_GAIN\x00-1.50 dB");
+    items.extend_from_slice(b"\x20\x00\x00\x00\x02\x00\x00\x00Cover Art (Front)\x00");
+    items.extend_from_slice(&[0xff, 0xff, 0x10, 0xc0]);
+    items.resize(items.len() + 28, 0);
+    let size = items.len() as u32 + 32;
+    let mut ape = ape_header(size, 2, 0xa000_0000);
+    ape.extend_from_slice(&items);
+    ape.extend_from_slice(&ape_header(size, 2, 0x8000_0000));
+
+    let lyrics3 = b"LYRICSBEGININD0000210LYR00005Hello000034LYRICS200";
+
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, 0);
+
+    // MPEG-1 Layer I, 32 kbps, 44.1 kHz, mono silence.
+    let mut frame = vec![0xff, 0xff, 0x10, 0xc0];
+    frame.resize(32, 0);
+
+    let mut data = frame.repeat(2);
+    data.extend_from_slice(&ape);
+    data.extend_from_slice(lyrics3);
+    data.extend_from_slice(&id3v1);
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.next_frame()?;
+    decoder.next_frame()?;
+    assert!(decoder.next_frame().is_err());
+    let ape_tag = decoder.ape_tag().unwrap();
+    assert_eq!(ape_tag.text("ReplayGain_Track_Gain"), Some("-1.50 dB"));
+    assert!(matches!(
+        ape_tag.get("Cover Art (Front)").map(|item| &item.value),
+        Some(ApeValue::Binary(data)) if data.len() == 32
+    ));
+    assert_eq!(decoder.lyrics3().unwrap().get("LYR"), Some("Hello"));
+    assert!(decoder.id3v1().is_some());
+
+    // Tags can also be parsed from the footer.
+    assert_eq!(ApeTag::parse(&ape[32..])?, *ape_tag);
+
+    // Tags without a header are found from their footer, rather than being
+    // skipped as invalid data.
+    let mut data = frame.repeat(2);
+    data.extend_from_slice(&items);
+    data.extend_from_slice(&ape_header(size, 2, 0));
+    data.extend_from_slice(&id3v1);
+
+    let decoder = Mp3Decoder::new(&data[..]);
+    assert_eq!(decoder.frames().count(), 2);
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    while decoder.next_frame().is_ok() {}
+    assert_eq!(decoder.ape_tag(), Some(ape_tag));
+    assert!(decoder.id3v1().is_some());
+    assert_eq!(decoder.skipped_bytes(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_xing_header() -> Result<(), Box<dyn std::error::Error>> {
+    // MPEG-1 Layer III, 128 kbps, 44.1 kHz, mono silence.
+    let mut frame = vec![0xff, 0xfb, 0x90, 0xc0];
+    frame.resize(417, 0);
+
+    // The Xing header follows the 17 bytes of side info.
+    let mut xing_frame = frame.clone();
+    let mut xing = b"Xing\x00\x00\x00\x0f\x00\x00\x00\x02\x00\x00\x04\x8d".to_vec();
+    xing.extend(0..100);
+    xing.extend_from_slice(&[0, 0, 0, 50]);
+    xing_frame[21..21 + xing.len()].copy_from_slice(&xing);
+
+    let mut data = xing_frame;
+    data.extend_from_slice(&frame.repeat(2));
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.next_frame()?;
+    let xing_header = decoder.xing_header().unwrap();
+    assert!(!xing_header.is_info);
+    assert_eq!(xing_header.frames, Some(2));
+    assert_eq!(xing_header.bytes, Some(1165));
+    assert_eq!(xing_header.toc.map(|toc| toc[99]), Some(99));
+    assert_eq!(xing_header.quality, Some(50));
+    assert_eq!(decoder.frames().count(), 1);
+    Ok(())
+}
+
+/// Computes the CRC-16/ARC checksum used by the LAME tag.
+fn crc16_arc(data: &[u8]) -> u16 {
+    data.iter().fold(0, |mut crc, &byte| {
+        crc ^= u16::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xa001
+            } else {
+                crc >> 1
+            };
+        }
+        crc
+    })
+}
+
+#[test]
+fn test_lame_tag() -> Result<(), Box<dyn std::error::Error>> {
+    // MPEG-1 Layer III, 128 kbps, 44.1 kHz, mono silence.
+    let mut frame = vec![0xff, 0xfb, 0x90, 0xc0];
+    frame.resize(417, 0);
+
+    let mut info_frame = frame.clone();
+    let mut info = b"Info\x00\x00\x00\x0f".to_vec();
+    info.resize(120, 0);
+    info.extend_from_slice(b"LAME3.100");
+    info.extend_from_slice(&[0x03, 200, 0x00, 0x80, 0x00, 0x00, 0x2e, 0x41, 0x00, 0x00]);
+    info.extend_from_slice(&[0x00, 0x80, 0x24, 0x03, 0xe8, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    info.extend_from_slice(&[0x00, 0x05, 0x1b, 0x12, 0x34]);
+    info_frame[21..21 + info.len()].copy_from_slice(&info);
+    let crc = crc16_arc(&info_frame[..21 + info.len()]);
+    info_frame[21 + info.len()..21 + info.len() + 2].copy_from_slice(&crc.to_be_bytes());
+
+    let mut data = info_frame.clone();
+    data.extend_from_slice(&frame);
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.next_frame()?;
+    assert!(decoder.xing_header().unwrap().is_info);
+    let lame_tag = decoder.lame_tag().unwrap();
+    assert_eq!(lame_tag.encoder, "LAME3.100");
+    assert_eq!(lame_tag.vbr_method, 3);
+    assert_eq!(lame_tag.lowpass, Some(20_000));
+    assert_eq!(lame_tag.peak, Some(1.0));
+    assert_eq!(lame_tag.track_gain, Some(-6.5));
+    assert_eq!(lame_tag.album_gain, None);
+    assert_eq!(lame_tag.encoder_delay, 576);
+    assert_eq!(lame_tag.encoder_padding, 1000);
+    assert_eq!(lame_tag.music_length, 1307);
+    assert_eq!(lame_tag.music_crc, 0x1234);
+
+    // Tags with an invalid CRC are ignored.
+    data[21 + 120 + 20] ^= 1;
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.next_frame()?;
+    assert!(decoder.xing_header().is_some());
+    assert!(decoder.lame_tag().is_none());
+    Ok(())
+}
+
+/// Builds an MPEG-1 Layer III frame containing a Xing header and LAME tag.
+fn lame_frame(frames: u32, delay: u16, padding: u16) -> Vec<u8> {
+    let mut frame = vec![0xff, 0xfb, 0x90, 0xc0];
+    frame.resize(21, 0);
+    frame.extend_from_slice(b"Xing\x00\x00\x00\x01");
+    frame.extend_from_slice(&frames.to_be_bytes());
+    frame.extend_from_slice(b"LAME3.100");
+    frame.resize(frame.len() + 12, 0);
+    let delay_padding = u32::from(delay) << 12 | u32::from(padding);
+    frame.extend_from_slice(&delay_padding.to_be_bytes()[1..]);
+    frame.resize(frame.len() + 10, 0);
+    let crc = crc16_arc(&frame);
+    frame.extend_from_slice(&crc.to_be_bytes());
+    frame.resize(417, 0);
+    frame
+}
+
+#[test]
+fn test_gapless() -> Result<(), Box<dyn std::error::Error>> {
+    // MPEG-1 Layer III, 128 kbps, 44.1 kHz, mono silence.
+    let mut frame = vec![0xff, 0xfb, 0x90, 0xc0];
+    frame.resize(417, 0);
+
+    let mut data = lame_frame(3, 576, 1000);
+    data.extend_from_slice(&frame.repeat(3));
+
+    // The decoder delay of 529 samples is added to the encoder delay.
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.set_gapless(true);
+    let num_samples: Vec<_> = decoder.frames().map(|frame| frame.num_samples).collect();
+    assert_eq!(num_samples, [1152 - 576 - 529, 1152, 1152 - 1000 + 529]);
+
+    let (_, samples) = puremp3::read_mp3_gapless(&data[..])?;
+    assert_eq!(samples.count(), 3 * 1152 - 576 - 1000);
+    let (_, samples) = puremp3::read_mp3(&data[..])?;
+    assert_eq!(samples.count(), 3 * 1152);
+
+    // iTunes stores the delay and length in an ID3v2 comment.
+    let mut comment = b"COMM\x00\x00\x00\x00\x00\x00\x00engiTunSMPB\x00".to_vec();
+    comment.extend_from_slice(b" 00000000 00000210 00000000 0000000000000800");
+    comment[7] = comment.len() as u8 - 10;
+    let mut data = id3v2_tag(3, 0, &comment);
+    data.extend_from_slice(&frame.repeat(3));
+
+    let (_, samples) = puremp3::read_mp3_gapless(&data[..])?;
+    assert_eq!(samples.count(), 0x800);
+    Ok(())
+}
+
+#[test]
+fn test_vbri_header() -> Result<(), Box<dyn std::error::Error>> {
+    // MPEG-1 Layer III, 128 kbps, 44.1 kHz, mono silence.
+    let mut frame = vec![0xff, 0xfb, 0x90, 0xc0];
+    frame.resize(417, 0);
+
+    // The VBRI header is always 32 bytes after the frame header.
+    let mut vbri_frame = frame.clone();
+    let mut vbri = b"VBRI\x00\x01\x02\x41\x00\x4b\x00\x00\x04\x8d\x00\x00\x00\x02".to_vec();
+    vbri.extend_from_slice(b"\x00\x02\x00\x02\x00\x02\x00\x01\x00\xd1\x00\xd0");
+    vbri_frame[36..36 + vbri.len()].copy_from_slice(&vbri);
+
+    let mut data = vbri_frame;
+    data.extend_from_slice(&frame.repeat(2));
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.next_frame()?;
+    assert!(decoder.xing_header().is_none());
+    let vbri_header = decoder.vbri_header().unwrap();
+    assert_eq!(vbri_header.version, 1);
+    assert_eq!(vbri_header.delay, 577);
+    assert_eq!(vbri_header.quality, 75);
+    assert_eq!(vbri_header.bytes, 1165);
+    assert_eq!(vbri_header.frames, 2);
+    assert_eq!(vbri_header.toc_scale, 2);
+    assert_eq!(vbri_header.frames_per_entry, 1);
+    assert_eq!(vbri_header.toc, [209, 208]);
+    assert_eq!(decoder.frames().count(), 1);
+
+    // The offset is counted from the start of the frame, including the CRC.
+    let mut crc_frame = frame.clone();
+    crc_frame[1] = 0xfa;
+    crc_frame[36..36 + vbri.len()].copy_from_slice(&vbri);
+    let mut data = crc_frame;
+    data.extend_from_slice(&frame.repeat(2));
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.next_frame()?;
+    assert_eq!(decoder.vbri_header().unwrap().toc, [209, 208]);
+    assert_eq!(decoder.frames().count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_length() -> Result<(), Box<dyn std::error::Error>> {
+    use puremp3::Length;
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    // MPEG-1 Layer III, 128 kbps and 160 kbps, 44.1 kHz, mono silence.
+    let mut frame = vec![0xff, 0xfb, 0x90, 0xc0];
+    frame.resize(417, 0);
+    let mut frame_160 = vec![0xff, 0xfb, 0xa0, 0xc0];
+    frame_160.resize(522, 0);
+
+    // The frame count is read from the LAME tag's Xing header.
+    let mut data = lame_frame(2, 576, 1000);
+    data.extend_from_slice(&frame.repeat(2));
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    assert_eq!(decoder.total_samples()?, Length::Exact(2304));
+    assert_eq!(
+        decoder.duration()?,
+        Length::Exact(Duration::from_nanos(52_244_897))
+    );
+    decoder.set_gapless(true);
+    assert_eq!(decoder.total_samples()?, Length::Exact(2304 - 576 - 1000));
+    assert_eq!(decoder.frames().count(), 2);
+
+    // CBR streams are estimated from the size of the stream.
+    let mut data = frame.repeat(20);
+    data.extend_from_slice(b"TAG");
+    data.resize(data.len() + 125, 0);
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    assert_eq!(decoder.total_samples()?, Length::Estimated(22_987));
+    assert_eq!(decoder.frames().count(), 20);
+
+    // Other trailing tags are not counted as audio either.
+    let mut ape_tag = Vec::new();
+    for &flags in &[0xa000_0000u32, 0x8000_0000] {
+        ape_tag.extend_from_slice(b"APETAGEX");
+        for n in &[2000, 32 + 15, 1, flags] {
+            ape_tag.extend_from_slice(&n.to_le_bytes());
+        }
+        ape_tag.resize(ape_tag.len() + 8, 0);
+        if ape_tag.len() == 32 {
+            ape_tag.extend_from_slice(b"\x01\x00\x00\x00\x00\x00\x00\x00Title\x00x");
+        }
+    }
+    let mut data = frame.repeat(20);
+    data.extend_from_slice(&ape_tag);
+    data.extend_from_slice(b"LYRICSBEGININD0000210LYR00005Hello000034LYRICS200");
+    data.extend_from_slice(b"TAG");
+    data.resize(data.len() + 125, 0);
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    assert_eq!(decoder.total_samples()?, Length::Estimated(22_987));
+    assert_eq!(decoder.frames().count(), 20);
+
+    // Other streams are scanned.
+    let mut data = [&frame[..], &frame_160[..]].concat().repeat(10);
+    data.truncate(data.len() - 1);
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    assert_eq!(decoder.total_samples()?, Length::Exact(19 * 1152));
+    assert_eq!(decoder.frames().count(), 19);
+    Ok(())
+}
+
+/// Builds a non-silent MPEG-1 Layer III stream, 128 kbps, 44.1 kHz, mono.
+///
+/// Each granule codes a single spectral line, and every frame after the first
+/// takes its main data from the bit reservoir of the previous frame.
+fn reservoir_frames(num_frames: usize) -> Vec<u8> {
+    fn write_bits(data: &mut [u8], pos: &mut usize, value: u32, len: usize) {
+        for i in (0..len).rev() {
+            if value >> i & 1 != 0 {
+                data[*pos / 8] |= 0x80 >> (*pos % 8);
+            }
+            *pos += 1;
+        }
+    }
+
+    let mut frames = vec![vec![0u8; 417]; num_frames];
+    for i in 0..num_frames {
+        frames[i][..4].copy_from_slice(&[0xff, 0xfb, 0x90, 0xc0]);
+        let main_data_begin = if i == 0 { 0 } else { 100 };
+        let mut pos = 0;
+        let side_info = &mut frames[i][4..21];
+        write_bits(side_info, &mut pos, main_data_begin, 9);
+        pos += 9;
+        for granule in 0..2 {
+            // part2_3_length, big_values, global_gain.
+            write_bits(side_info, &mut pos, 5, 12);
+            pos += 9;
+            write_bits(
+                side_info,
+                &mut pos,
+                170 + (i as u32 * 7 + granule * 13) % 40,
+                8,
+            );
+            pos += 4 + 1 + 15 + 4 + 3 + 1 + 1;
+            // count1table_select
+            write_bits(side_info, &mut pos, 1, 1);
+        }
+
+        // Count1 table B codes (0, 0, 0, 1) as 1110, followed by a sign bit.
+        let main_data = if i == 0 {
+            &mut frames[0][21..23]
+        } else {
+            &mut frames[i - 1][317..319]
+        };
+        let mut pos = 0;
+        write_bits(main_data, &mut pos, 0b1110 << 1 | (i as u32 & 1), 5);
+        write_bits(main_data, &mut pos, 0b1110 << 1, 5);
+    }
+    frames.concat()
+}
+
+#[test]
+fn test_seek() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    fn decode<R: std::io::Read>(decoder: &mut Mp3Decoder<R>) -> Vec<f32> {
+        let mut samples = Vec::new();
+        while let Ok(frame) = decoder.next_frame() {
+            samples.extend_from_slice(&frame.samples[0][..frame.num_samples]);
+        }
+        samples
+    }
+
+    let data = reservoir_frames(12);
+    let samples = decode(&mut Mp3Decoder::new(&data[..]));
+    assert_eq!(samples.len(), 12 * 1152);
+    assert!(samples.iter().any(|&sample| sample != 0.0));
+
+    // Seeking restores the bit reservoir and filterbank state, so the output
+    // matches a linear decode.
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    for &sample in &[5000, 0, 1152 * 7, 1152 * 11 + 1, 1152 * 12, 20_000] {
+        decoder.seek_to_sample(sample)?;
+        let expected = &samples[usize::min(sample as usize, samples.len())..];
+        assert_eq!(decode(&mut decoder), expected);
+    }
+    decoder.seek_to_time(Duration::from_millis(100))?;
+    assert_eq!(decode(&mut decoder), &samples[4410..]);
+
+    // Gapless positions do not include the encoder delay.
+    let mut data = lame_frame(12, 576, 1000);
+    data.extend_from_slice(&reservoir_frames(12));
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    decoder.set_gapless(true);
+    let samples = decode(&mut decoder);
+    assert_eq!(samples.len(), 12 * 1152 - 576 - 1000);
+    for &sample in &[0, 3000, 1152 * 10] {
+        decoder.seek_to_sample(sample)?;
+        assert_eq!(decode(&mut decoder), &samples[sample as usize..]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_frame_index() -> Result<(), Box<dyn std::error::Error>> {
+    use puremp3::FrameIndex;
+    use std::io::Cursor;
+
+    let mut data = lame_frame(12, 576, 1000);
+    data.extend_from_slice(&reservoir_frames(12));
+    data.splice(417 * 4..417 * 4, b"junk".iter().copied());
+
+    let index = FrameIndex::build(&data[..])?;
+    assert_eq!(index.frames().len(), 12);
+    assert_eq!(index.total_samples(), 12 * 1152);
+    let offsets: Vec<_> = index.frames().iter().map(|frame| frame.offset).collect();
+    assert_eq!(offsets[..5], [417, 834, 1251, 1672, 2089]);
+    let frame = &index.frames()[3];
+    assert_eq!(frame.sample, 3 * 1152);
+    assert_eq!(frame.main_data_begin, 100);
+    assert_eq!(frame.header.bitrate, BitRate::Kbps128);
+    assert_eq!(index.find_frame(3 * 1152 + 1151), Some(3));
+    assert_eq!(index.find_frame(12 * 1152), None);
+
+    // Repeated headers are stored once, so each frame takes a few bytes.
+    let bytes = index.to_bytes();
+    assert!(bytes.len() < 48);
+    let loaded = FrameIndex::from_bytes(&bytes)?;
+    assert_eq!(loaded.to_bytes(), bytes);
+    assert_eq!(loaded.frames()[4].offset, 2089);
+    assert_eq!(loaded.frames()[4].sample, 4 * 1152);
+    assert!(FrameIndex::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(FrameIndex::from_bytes(b"MP3").is_err());
+
+    // A loaded index is used for seeking instead of scanning the stream.
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    decoder.set_gapless(true);
+    let mut expected = Vec::new();
+    while let Ok(frame) = decoder.next_frame() {
+        expected.extend_from_slice(&frame.samples[0][..frame.num_samples]);
+    }
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    decoder.set_gapless(true);
+    decoder.set_frame_index(loaded);
+    decoder.seek_to_sample(4000)?;
+    let mut samples = Vec::new();
+    while let Ok(frame) = decoder.next_frame() {
+        samples.extend_from_slice(&frame.samples[0][..frame.num_samples]);
+    }
+    assert_eq!(samples, &expected[4000..]);
+    Ok(())
+}
+
+#[test]
+fn test_seek_approximate() -> Result<(), Box<dyn std::error::Error>> {
+    use puremp3::Length;
+    use std::io::Cursor;
+
+    fn decode<R: std::io::Read>(decoder: &mut Mp3Decoder<R>) -> Vec<f32> {
+        let mut samples = Vec::new();
+        while let Ok(frame) = decoder.next_frame() {
+            samples.extend_from_slice(&frame.samples[0][..frame.num_samples]);
+        }
+        samples
+    }
+
+    // Without a seek table, the position is estimated from the bit rate.
+    let data = reservoir_frames(40);
+    let samples = decode(&mut Mp3Decoder::new(&data[..]));
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    for &target in &[0, 1152 * 10 + 500, 1152 * 30] {
+        let sample = decoder.seek_to_sample_approximate(target)?;
+        assert_eq!(sample.is_exact(), target == 0);
+        let sample = sample.value();
+        assert!(sample >= target && sample < target + 2 * 1152);
+        assert_eq!(decode(&mut decoder), &samples[sample as usize..]);
+    }
+
+    // The Xing seek table maps percentages of the duration to byte positions.
+    let mut xing_frame = vec![0xff, 0xfb, 0x90, 0xc0];
+    xing_frame.resize(21, 0);
+    xing_frame.extend_from_slice(b"Xing\x00\x00\x00\x07");
+    xing_frame.extend_from_slice(&100u32.to_be_bytes());
+    xing_frame.extend_from_slice(&(101u32 * 417).to_be_bytes());
+    xing_frame.extend((0..100).map(|i| ((i + 1) * 256 / 101) as u8));
+    xing_frame.resize(417, 0);
+    let mut data = xing_frame;
+    data.extend_from_slice(&reservoir_frames(100));
+    let samples = decode(&mut Mp3Decoder::new(&data[..]));
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    for &target in &[1152 * 10 + 500, 1152 * 50, 1152 * 97] {
+        let sample = decoder.seek_to_sample_approximate(target)?;
+        assert_eq!(sample, Length::Estimated(target));
+        assert_eq!(decode(&mut decoder), &samples[target as usize..]);
+    }
+    decoder.seek_to_sample_approximate(1152 * 200)?;
+    assert!(decode(&mut decoder).is_empty());
+
+    // With a skewed seek table the estimate is wrong, but the frame index
+    // gives the sample that was actually reached.
+    for (i, position) in data[37..137].iter_mut().enumerate() {
+        *position = ((i + 1) * 128 / 101) as u8;
+    }
+    let mut decoder = Mp3Decoder::new(Cursor::new(&data[..]));
+    for &target in &[1152 * 10, 1152 * 50] {
+        let sample = decoder.seek_to_sample_approximate(target)?;
+        assert!(!sample.is_exact());
+        assert_ne!(decode(&mut decoder), &samples[sample.value() as usize..]);
+    }
+    decoder.frame_index()?;
+    for &target in &[1152 * 10, 1152 * 50, 1152 * 90] {
+        let sample = decoder.seek_to_sample_approximate(target)?;
+        assert!(sample.is_exact());
+        assert_eq!(decode(&mut decoder), &samples[sample.value() as usize..]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sync() -> Result<(), Box<dyn std::error::Error>> {
+    // MPEG-1 Layer III, 128 kbps, 44.1 kHz, mono silence.
+    let mut frame = vec![0xff, 0xfb, 0x90, 0xc0];
+    frame.resize(417, 0);
+
+    // Junk containing a frame header that is not followed by another frame.
+    let mut data = vec![0x12; 10];
+    data.extend_from_slice(&[0xff, 0xfb, 0x90, 0xc0]);
+    data.resize(100, 0);
+    data.extend_from_slice(&frame.repeat(3));
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    assert_eq!(decoder.sync_frames(), 3);
+    for _ in 0..3 {
+        decoder.next_frame()?;
+    }
+    assert!(decoder.next_frame().is_err());
+    assert_eq!(decoder.skipped_bytes(), 100);
+
+    // Accepting the first valid header syncs to the false frame.
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.set_sync_frames(1);
+    assert_eq!(decoder.next_frame()?.header.bitrate, BitRate::Kbps128);
+    assert_eq!(decoder.skipped_bytes(), 10);
+
+    // The search restarts at the next byte after a false sync, even when the
+    // frame header starts inside it.
+    let mut data = vec![0xff];
+    data.extend_from_slice(&frame.repeat(3));
+    let decoder = Mp3Decoder::new(&data[..]);
+    let skipped_bytes: Vec<_> = decoder.frames().map(|frame| frame.skipped_bytes).collect();
+    assert_eq!(skipped_bytes, [1, 0, 0]);
+    Ok(())
+}
+
+#[test]
+fn test_concealment() -> Result<(), Box<dyn std::error::Error>> {
+    use puremp3::Concealment;
+
+    // Set big_values of the first granule of the third frame to 511.
+    let mut data = reservoir_frames(6);
+    data[2 * 417 + 7] |= 0x03;
+    data[2 * 417 + 8] |= 0xfe;
+
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    decoder.next_frame()?;
+    decoder.next_frame()?;
+    assert!(matches!(
+        decoder.next_frame(),
+        Err(Error::Mp3Error(Mp3Error::InvalidData(_)))
+    ));
+    assert!(decoder.next_frame().is_ok());
+
+    let decode = |data: &[u8], concealment| {
+        let mut decoder = Mp3Decoder::new(data);
+        decoder.set_concealment(concealment);
+        decoder.frames().collect::<Vec<_>>()
+    };
+
+    // Silence contains the decaying tail of the previous frame.
+    let frames = decode(&data, Concealment::Silence);
+    assert_eq!(frames.len(), 6);
+    let concealed: Vec<_> = frames.iter().map(|frame| frame.concealed).collect();
+    assert_eq!(concealed, [false, false, true, false, false, false]);
+    assert!(frames[2].samples[0][..576].iter().any(|&s| s != 0.0));
+    assert!(frames[2].samples[0][1088..].iter().all(|&s| s == 0.0));
+
+    let frames = decode(&data, Concealment::Repeat);
+    assert_eq!(frames[2].samples[0][..576], frames[1].samples[0][576..]);
+    assert_eq!(frames[2].samples[0][576..], frames[1].samples[0][576..]);
+
+    let frames = decode(&data, Concealment::Fade);
+    assert_eq!(frames[2].samples[0][0], frames[1].samples[0][576]);
+    assert_eq!(frames[2].samples[0][576], frames[1].samples[0][576] * 0.5);
+    assert_eq!(frames[3].samples[0][0], 0.0);
+
+    // Truncated frames are concealed as well.
+    let frames = decode(&data[..data.len() - 200], Concealment::Silence);
+    assert_eq!(frames.len(), 6);
+    assert!(frames[5].concealed);
+
+    // A read error while skipping a truncated frame is returned.
+    struct FailingReader<'a>(&'a [u8], usize);
+    impl std::io::Read for FailingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                // Fail once, when skipping the rest of the last frame.
+                self.1 += 1;
+                if self.1 == 3 {
+                    return Err(std::io::ErrorKind::Other.into());
+                }
+            }
+            self.0.read(buf)
+        }
+    }
+    let mut decoder = Mp3Decoder::new(FailingReader(&data[..data.len() - 200], 0));
+    decoder.set_concealment(Concealment::Silence);
+    for _ in 0..5 {
+        decoder.next_frame()?;
+    }
+    assert!(matches!(
+        decoder.next_frame(),
+        Err(Error::IoError(ref e)) if e.kind() == std::io::ErrorKind::Other
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_reservoir_underflow() -> Result<(), Box<dyn std::error::Error>> {
+    use puremp3::Concealment;
+
+    // The first frame refers to main data in a frame that was cut off.
+    let data = reservoir_frames(6);
+    let data = &data[417..];
+
+    let mut decoder = Mp3Decoder::new(data);
+    assert!(matches!(
+        decoder.next_frame(),
+        Err(Error::Mp3Error(Mp3Error::ReservoirUnderflow))
+    ));
+    assert!(decoder.next_frame()?.samples[0].iter().any(|&s| s != 0.0));
+
+    // The iterators skip the frame.
+    assert_eq!(Mp3Decoder::new(data).frames().count(), 4);
+    let (_, samples) = puremp3::read_mp3(data)?;
+    assert_eq!(samples.count(), 4 * 1152);
+
+    // The frame can also be concealed.
+    let mut decoder = Mp3Decoder::new(data);
+    decoder.set_concealment(Concealment::Silence);
+    let frames: Vec<_> = decoder.frames().collect();
+    assert_eq!(frames.len(), 5);
+    assert!(frames[0].concealed);
+    assert!(frames[0].samples[0].iter().all(|&s| s == 0.0));
+    let (_, samples) = puremp3::read_mp3_with_concealment(data, Concealment::Silence)?;
+    let samples: Vec<_> = samples.collect();
+    assert_eq!(samples.len(), 5 * 1152);
+    assert!(samples[..1152].iter().all(|&s| s == (0.0, 0.0)));
+    Ok(())
+}
+
+#[test]
+fn test_bad_frames() -> Result<(), Box<dyn std::error::Error>> {
+    // Overwrites a field in the side info of the first granule of the third frame.
+    // Offsets are in bits from the start of the granule's side info.
+    fn set_field(data: &mut [u8], offset: usize, value: u32, len: usize) {
+        let start = (2 * 417 + 4) * 8 + 18 + offset;
+        for i in 0..len {
+            let (byte, bit) = ((start + i) / 8, 0x80 >> ((start + i) % 8));
+            if value >> (len - 1 - i) & 1 != 0 {
+                data[byte] |= bit;
+            } else {
+                data[byte] &= !bit;
+            }
+        }
+    }
+    const PART2_3_LENGTH: usize = 0;
+    const BIG_VALUES: usize = 12;
+    const SCALEFAC_COMPRESS: usize = 29;
+    const WINDOW_SWITCHING: usize = 33;
+    const BLOCK_TYPE: usize = 34;
+    const MIXED_BLOCK: usize = 36;
+    const REGION0_COUNT: usize = 49;
+    const REGION1_COUNT: usize = 53;
+
+    // The offset, value and length of each field to overwrite.
+    type Fields<'a> = &'a [(usize, u32, usize)];
+    let cases: &[(&str, Fields, bool)] = &[
+        ("forbidden block type", &[(WINDOW_SWITCHING, 1, 1)], true),
+        (
+            "mixed long block",
+            &[
+                (WINDOW_SWITCHING, 1, 1),
+                (BLOCK_TYPE, 0b01, 2),
+                (MIXED_BLOCK, 1, 1),
+            ],
+            true,
+        ),
+        (
+            "scale factors exceed part2_3_length",
+            &[(SCALEFAC_COMPRESS, 15, 4)],
+            true,
+        ),
+        (
+            "regions past the last band",
+            &[
+                (BIG_VALUES, 288, 9),
+                (REGION0_COUNT, 15, 4),
+                (REGION1_COUNT, 7, 3),
+            ],
+            false,
+        ),
+        (
+            "mixed short block",
+            &[
+                (WINDOW_SWITCHING, 1, 1),
+                (BLOCK_TYPE, 0b10, 2),
+                (MIXED_BLOCK, 1, 1),
+            ],
+            false,
+        ),
+        ("empty granule", &[(PART2_3_LENGTH, 0, 12)], false),
+    ];
+
+    for &(name, fields, is_err) in cases {
+        let mut data = reservoir_frames(4);
+        for &(offset, value, len) in fields {
+            set_field(&mut data, offset, value, len);
+        }
+
+        let mut decoder = Mp3Decoder::new(&data[..]);
+        decoder.next_frame()?;
+        decoder.next_frame()?;
+        let result = decoder.next_frame();
+        if is_err {
+            assert!(
+                matches!(result, Err(Error::Mp3Error(Mp3Error::InvalidData(_)))),
+                "{}",
+                name
+            );
+        } else {
+            assert!(result.is_ok(), "{}", name);
+        }
+
+        // The bit reservoir stays intact for the following frame.
+        assert!(decoder.next_frame().is_ok(), "{}", name);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_push_decoder() -> Result<(), Box<dyn std::error::Error>> {
+    use puremp3::PushDecoder;
+
+    fn decode_all(decoder: &mut PushDecoder) -> Result<Vec<Frame>, Error> {
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.decode()? {
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+
+    let mut data = id3v2_tag(3, 0, &[]);
+    data.extend_from_slice(&[0xff, 0xfb, 0x00, 0x12, 0x34]);
+    data.extend_from_slice(&reservoir_frames(8));
+    // Trailing tags are skipped rather than counted as invalid data.
+    data.extend_from_slice(b"LYRICSBEGININD0000210LYR00005Hello000034LYRICS200");
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, 0);
+    data.extend_from_slice(&id3v1);
+    let expected: Vec<_> = Mp3Decoder::new(&data[..]).frames().collect();
+    assert_eq!(expected.len(), 8);
+
+    // The first frame is only decoded once the following frames are validated.
+    let start = 10 + 5;
+    let mut decoder = PushDecoder::new();
+    decoder.feed(&data[..start + 2 * 417]);
+    assert!(decoder.decode()?.is_none());
+    decoder.feed(&data[start + 2 * 417..data.len() - 300]);
+    assert_eq!(decode_all(&mut decoder)?.len(), 7);
+    decoder.feed(&data[data.len() - 300..]);
+    assert_eq!(decode_all(&mut decoder)?.len(), 1);
+    decoder.finish();
+    assert!(decoder.decode()?.is_none());
+    assert_eq!(decoder.buffered_len(), 0);
+
+    // The output does not depend on how the data is split.
+    for &chunk_size in &[1, 7, 416, 1000, data.len()] {
+        let mut decoder = PushDecoder::new();
+        let mut frames = Vec::new();
+        for chunk in data.chunks(chunk_size) {
+            decoder.feed(chunk);
+            frames.extend(decode_all(&mut decoder)?);
+        }
+        decoder.finish();
+        frames.extend(decode_all(&mut decoder)?);
+
+        assert_eq!(frames.len(), expected.len(), "{}", chunk_size);
+        assert_eq!(frames[0].skipped_bytes, 5);
+        assert_eq!(decoder.skipped_bytes(), 5);
+        for (frame, expected) in frames.iter().zip(&expected) {
+            assert_eq!(&frame.samples[..], &expected.samples[..]);
+        }
+    }
+
+    // Joining part way through a frame, and ending part way through another.
+    let data = reservoir_frames(8);
+    let data = &data[2 * 417 + 100..data.len() - 100];
+    let expected: Vec<_> = Mp3Decoder::new(data).frames().collect();
+    assert_eq!(expected.len(), 3);
+    let mut decoder = PushDecoder::new();
+    let mut results = Vec::new();
+    for chunk in data.chunks(300) {
+        decoder.feed(chunk);
+        while let Some(result) = decoder.decode().transpose() {
+            results.push(result);
+        }
+    }
+    decoder.finish();
+    while let Some(result) = decoder.decode().transpose() {
+        results.push(result);
+    }
+    assert!(matches!(
+        results[0],
+        Err(Error::Mp3Error(Mp3Error::ReservoirUnderflow))
+    ));
+    assert!(matches!(
+        results[4],
+        Err(Error::IoError(ref e)) if e.kind() == std::io::ErrorKind::UnexpectedEof
+    ));
+    assert_eq!(results.len(), 5);
+    for (result, expected) in results[1..4].iter().zip(&expected) {
+        assert_eq!(&result.as_ref().unwrap().samples[..], &expected.samples[..]);
+    }
+    assert_eq!(decoder.skipped_bytes(), 417 - 100);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_decoder() -> Result<(), Box<dyn std::error::Error>> {
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    use futures::stream::TryStreamExt;
+    use puremp3::AsyncMp3Decoder;
+
+    let data = reservoir_frames(8);
+    let expected: Vec<_> = Mp3Decoder::new(&data[..]).frames().collect();
+
+    let decoder = AsyncMp3Decoder::new(Cursor::new(&data[..]));
+    let frames: Vec<_> = block_on(decoder.try_collect())?;
+    assert_eq!(frames.len(), expected.len());
+    for (frame, expected) in frames.iter().zip(&expected) {
+        assert_eq!(&frame.samples[..], &expected.samples[..]);
+    }
+
+    // Once in sync, each read is sized to the next frame.
+    struct RecordingReader<'a>(Cursor<&'a [u8]>, Vec<usize>);
+    impl futures::io::AsyncRead for RecordingReader<'_> {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+            buf: &mut [u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            self.1.push(buf.len());
+            std::pin::Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+
+    let data = reservoir_frames(20);
+    let mut decoder = AsyncMp3Decoder::new(RecordingReader(Cursor::new(&data[..]), Vec::new()));
+    let mut num_frames = 0;
+    while let Some(frame) = block_on(decoder.try_next())? {
+        assert_eq!(frame.num_samples, 1152);
+        num_frames += 1;
+    }
+    assert_eq!(num_frames, 20);
+    let reads = &decoder.get_ref().1;
+    assert!(reads[..reads.len() - 1]
+        .iter()
+        .rev()
+        .take(10)
+        .all(|&len| len == 417));
+    Ok(())
+}
+
+#[test]
+fn test_decode_frame_into() -> Result<(), Box<dyn std::error::Error>> {
+    use puremp3::MAX_FRAME_SAMPLES;
+
+    let data = reservoir_frames(8);
+    for &gapless in &[false, true] {
+        let mut decoder = Mp3Decoder::new(&data[..]);
+        decoder.set_gapless(gapless);
+        let expected: Vec<_> = decoder.frames().collect();
+        assert!(!expected.is_empty());
+
+        let mut decoder = Mp3Decoder::new(&data[..]);
+        decoder.set_gapless(gapless);
+        let mut samples = vec![0f32; 2 * MAX_FRAME_SAMPLES];
+        for expected in &expected {
+            let (header, num_samples) = decoder.decode_frame_into(&mut samples)?;
+            assert_eq!(header.sample_rate, expected.header.sample_rate);
+            assert_eq!(num_samples, expected.num_samples);
+            for (i, sample) in samples[..2 * num_samples].chunks(2).enumerate() {
+                assert_eq!(sample, &[expected.samples[0][i], expected.samples[1][i]]);
+            }
+        }
+        assert!(decoder.decode_frame_into(&mut samples).is_err());
+
+        let mut decoder = Mp3Decoder::new(&data[..]);
+        decoder.set_gapless(gapless);
+        let mut left = [0f32; MAX_FRAME_SAMPLES];
+        let mut right = [0f32; MAX_FRAME_SAMPLES];
+        for expected in &expected {
+            let (_, num_samples) = decoder.decode_frame_into_planar(&mut left, &mut right)?;
+            assert_eq!(num_samples, expected.num_samples);
+            assert_eq!(&left[..num_samples], &expected.samples[0][..num_samples]);
+            assert_eq!(&right[..num_samples], &expected.samples[1][..num_samples]);
+        }
+        assert!(decoder
+            .decode_frame_into_planar(&mut left, &mut right)
+            .is_err());
+    }
+
+    // Buffers that cannot hold a frame are rejected before decoding.
+    let mut decoder = Mp3Decoder::new(&data[..]);
+    let mut samples = vec![0f32; 2 * MAX_FRAME_SAMPLES - 1];
+    assert!(matches!(
+        decoder.decode_frame_into(&mut samples),
+        Err(Error::IoError(ref e)) if e.kind() == std::io::ErrorKind::InvalidInput
+    ));
+    assert!(matches!(
+        decoder.decode_frame_into_planar(&mut samples, &mut [0f32; 1]),
+        Err(Error::IoError(ref e)) if e.kind() == std::io::ErrorKind::InvalidInput
+    ));
+    let expected = Mp3Decoder::new(&data[..]).next_frame()?;
+    assert_eq!(&decoder.next_frame()?.samples[..], &expected.samples[..]);
+    Ok(())
+}
+
+#[test]
+fn test_fuzz_regressions() {
+    // Inputs found by the fuzz targets in `fuzz/` that caused panics.
+    let inputs: &[&[u8]] = &[
+        // A free format frame size measured from a frame with a smaller header.
+        &[
+            0xb5, 0x6f, 0xff, 0xff, 0x73, 0xff, 0xff, 0xff, 0x00, 0x55, 0x7a, 0xff, 0xff, 0x00,
+            0x47, 0xff, 0xff, 0xe2, 0x00, 0x55, 0xff, 0xff, 0x00, 0x47,
+        ],
+        // The same, while checking the frames that follow a header.
+        &[
+            0x54, 0xff, 0xf5, 0x00, 0xff, 0xe6, 0xff, 0xf5, 0x00, 0xff, 0xe6, 0xff, 0xff, 0x00,
+            0x00, 0xff, 0xff, 0xfe, 0x00, 0xff, 0xff, 0xe5, 0x47,
+        ],
+    ];
+
+    for input in inputs {
+        // Decode until the end of the stream.
+        let mut decoder = Mp3Decoder::new(*input);
+        while let Ok(_) | Err(Error::Mp3Error(_)) = decoder.next_frame() {}
+    }
+
+    // An APE footer with the no-footer flag set and a size smaller than a footer.
+    let mut footer = b"APETAGEX".to_vec();
+    for n in &[2000u32, 0, 0, 0x4000_0000] {
+        footer.extend_from_slice(&n.to_le_bytes());
+    }
+    footer.resize(32, 0);
+    assert!(puremp3::ape::ApeTag::parse(&footer).is_err());
+}